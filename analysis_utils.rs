@@ -0,0 +1,302 @@
+///
+/// # analysis_utils.rs
+/// This file contains the functions used to analyse a single track
+/// and the statistics saved in the 'stats.json' file.
+///
+/// ## Author
+/// * Tom Planche - <github.com/tomPlanche>
+
+// IMPORTS ===================================================================================================  IMPORTS
+use std::collections::HashSet;
+
+use serde::{Deserialize, Serialize};
+
+use crate::elevation_utils::{calc_ascent_descent, DEFAULT_ELEVATION_FILTER};
+use crate::gpx_utils::{calc_distance, find_common_indexes, Coord, DistanceUnit, Point, COMMON_COORDS_THRESHOLD};
+// END IMPORTS ==========================================================================================   END IMPORTS
+
+// VARIABLES ================================================================================================ VARIABLE
+// A track whose start and end are closer than this (in meters) is a loop.
+#[allow(dead_code)]
+pub const LOOP_THRESHOLD: f64 = 100.0;
+
+// A track retracing at least this part of its points is an out-and-back.
+#[allow(dead_code)]
+pub const OUT_AND_BACK_RATIO: f64 = 0.8;
+
+// Points closer than this along the track (in meters) are neighbours and are not compared.
+// A distance rather than a number of points, since the sampling differs between files.
+#[allow(dead_code)]
+pub const NEIGHBOUR_EXCLUSION: f64 = 50.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TrackShape {
+    Loop,
+    OutAndBack,
+    PointToPoint,
+    // Fewer than 2 points, nothing to classify
+    Unknown,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ShapeAnalysis {
+    pub shape: TrackShape,
+    pub start_end_gap: f64,
+    pub retrace_ratio: f64,
+}
+
+// Section of a track passing again near its own earlier points,
+// as inclusive index ranges: `second` is the later pass, `first` the earlier points it is close to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SelfOverlap {
+    pub first: (usize, usize),
+    pub second: (usize, usize),
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TrackStats {
+    pub points: usize,
+    pub length: f64,
+    // Length without the sections walked a second time
+    pub unique_length: f64,
+    // With the default elevation filter, 0 without elevation
+    pub ascent: f64,
+    pub descent: f64,
+    pub shape: ShapeAnalysis,
+    pub self_overlaps: Vec<SelfOverlap>,
+}
+// END VARIABLES =======================================================================================  END VARIABLES
+
+// FUNCTIONS ================================================================================================ FUNCTIONS
+///
+/// # calc_track_length
+/// Calculate the length of a track.
+///
+/// ## Arguments
+/// * `coords` - The coordinates of the track.
+///
+/// ## Returns
+/// * `f64` - The length of the track (in meters).
+#[allow(dead_code)]
+pub fn calc_track_length(coords: &[Coord]) -> f64 {
+    coords
+        .windows(2)
+        .map(|pair| calc_distance(pair[0], pair[1], DistanceUnit::Meters))
+        .sum()
+}
+
+///
+/// # calc_cumulative_distances
+/// Calculate the distance along the track from its start to each point.
+///
+/// ## Arguments
+/// * `coords` - The coordinates of the track.
+///
+/// ## Returns
+/// * `Vec<f64>` - The distance of each point from the start (in meters).
+#[allow(dead_code)]
+pub fn calc_cumulative_distances(coords: &[Coord]) -> Vec<f64> {
+    let mut distances: Vec<f64> = Vec::with_capacity(coords.len());
+    let mut total: f64 = 0.0;
+
+    for (index, coord) in coords.iter().enumerate() {
+        if index > 0 {
+            total += calc_distance(coords[index - 1], *coord, DistanceUnit::Meters);
+        }
+        distances.push(total);
+    }
+
+    distances
+}
+
+///
+/// # calc_retrace_ratio
+/// Compare a track with its reverse to find the part of its points walked twice.
+///
+/// ## Arguments
+/// * `coords` - The coordinates of the track.
+///
+/// ## Returns
+/// * `f64` - The ratio of points close to another, non neighbouring, point of the track.
+#[allow(dead_code)]
+pub fn calc_retrace_ratio(coords: &[Coord]) -> f64 {
+    if coords.is_empty() {
+        return 0.0;
+    }
+
+    let reversed: Vec<Coord> = coords.iter().rev().copied().collect();
+    let last = coords.len() - 1;
+    let distances: Vec<f64> = calc_cumulative_distances(coords);
+
+    let retraced: HashSet<usize> = find_common_indexes(coords, &reversed, COMMON_COORDS_THRESHOLD)
+        .into_iter()
+        .filter(|(index, reversed_index)| {
+            (distances[*index] - distances[last - reversed_index]).abs() > NEIGHBOUR_EXCLUSION
+        })
+        .map(|(index, _)| index)
+        .collect();
+
+    retraced.len() as f64 / coords.len() as f64
+}
+
+///
+/// # analyse_shape
+/// Find whether a track is a loop, an out-and-back or a point-to-point track.
+/// An out-and-back also ends where it starts, so it is checked first.
+/// A track of fewer than 2 points is `Unknown`.
+///
+/// ## Arguments
+/// * `coords` - The coordinates of the track.
+///
+/// ## Returns
+/// * `ShapeAnalysis` - The shape of the track and the values used to find it.
+#[allow(dead_code)]
+pub fn analyse_shape(coords: &[Coord]) -> ShapeAnalysis {
+    let start_end_gap: f64 = match (coords.first(), coords.last()) {
+        (Some(first), Some(last)) => calc_distance(*first, *last, DistanceUnit::Meters),
+        _ => 0.0,
+    };
+    let retrace_ratio: f64 = calc_retrace_ratio(coords);
+
+    let shape = if coords.len() < 2 {
+        TrackShape::Unknown
+    } else if retrace_ratio >= OUT_AND_BACK_RATIO {
+        TrackShape::OutAndBack
+    } else if start_end_gap < LOOP_THRESHOLD {
+        TrackShape::Loop
+    } else {
+        TrackShape::PointToPoint
+    };
+
+    ShapeAnalysis {
+        shape,
+        start_end_gap,
+        retrace_ratio,
+    }
+}
+
+///
+/// # find_self_overlaps
+/// Find the sections where a track passes within `radius` of its own earlier points.
+/// The points closer than `min_track_gap` along the track are its neighbours and are not compared.
+///
+/// ## Arguments
+/// * `coords` - The coordinates of the track.
+/// * `radius` - The maximum distance (in meters).
+/// * `min_track_gap` - The distance along the track under which points are neighbours (in meters).
+///
+/// ## Returns
+/// * `Vec<SelfOverlap>` - The overlapping sections, in the track order.
+#[allow(dead_code)]
+pub fn find_self_overlaps(coords: &[Coord], radius: f64, min_track_gap: f64) -> Vec<SelfOverlap> {
+    let mut overlaps: Vec<SelfOverlap> = Vec::new();
+    let mut last_matched: Option<usize> = None;
+    let distances: Vec<f64> = calc_cumulative_distances(coords);
+
+    for (index, coord) in coords.iter().enumerate() {
+        // The earlier points far enough along the track
+        let limit = distances.partition_point(|distance| distances[index] - distance > min_track_gap);
+
+        let matches: Vec<usize> = coords[..limit]
+            .iter()
+            .enumerate()
+            .filter(|(_, earlier)| calc_distance(*coord, **earlier, DistanceUnit::Meters) < radius)
+            .map(|(earlier_index, _)| earlier_index)
+            .collect();
+
+        let (Some(min), Some(max)) = (matches.iter().min(), matches.iter().max()) else {
+            continue;
+        };
+
+        // Continue the current section if the previous point (or the one before) matched too
+        match (overlaps.last_mut(), last_matched) {
+            (Some(overlap), Some(last)) if index - last <= 2 => {
+                overlap.second.1 = index;
+                overlap.first = (overlap.first.0.min(*min), overlap.first.1.max(*max));
+            }
+            _ => overlaps.push(SelfOverlap {
+                first: (*min, *max),
+                second: (index, index),
+            }),
+        }
+
+        last_matched = Some(index);
+    }
+
+    overlaps
+}
+
+///
+/// # calc_track_stats
+/// Calculate the statistics of a track.
+///
+/// ## Arguments
+/// * `points` - The points of the track.
+///
+/// ## Returns
+/// * `TrackStats` - The statistics of the track.
+#[allow(dead_code)]
+pub fn calc_track_stats(points: &[Point]) -> TrackStats {
+    let coords: Vec<Coord> = points.iter().map(|point| point.coords).collect();
+
+    let self_overlaps = find_self_overlaps(&coords, COMMON_COORDS_THRESHOLD, NEIGHBOUR_EXCLUSION);
+    let length = calc_track_length(&coords);
+    let (ascent, descent) = calc_ascent_descent(points, DEFAULT_ELEVATION_FILTER);
+
+    let walked_again: f64 = self_overlaps
+        .iter()
+        .map(|overlap| calc_track_length(&coords[overlap.second.0..=overlap.second.1]))
+        .sum();
+
+    TrackStats {
+        points: coords.len(),
+        length,
+        unique_length: length - walked_again,
+        ascent,
+        descent,
+        shape: analyse_shape(&coords),
+        self_overlaps,
+    }
+}
+
+#[cfg(test)]
+fn test_line(from: usize, to: usize) -> Vec<Coord> {
+    // Points every ~50m going north
+    (from..to).map(|i| Coord { lat: 42.0 + i as f64 * 0.00045, lon: 0.0 }).collect()
+}
+
+#[test]
+fn test_analyse_shape() {
+    let point_to_point = test_line(0, 40);
+    assert_eq!(analyse_shape(&point_to_point).shape, TrackShape::PointToPoint);
+
+    let out_and_back: Vec<Coord> = test_line(0, 40).into_iter().chain(test_line(0, 40).into_iter().rev()).collect();
+    assert_eq!(analyse_shape(&out_and_back).shape, TrackShape::OutAndBack);
+
+    // North, then east, then back south-west to the start
+    let mut loop_coords = test_line(0, 40);
+    loop_coords.extend((0..40).map(|i| Coord { lat: 42.0 + 39.0 * 0.00045, lon: i as f64 * 0.0006 }));
+    loop_coords.extend((0..40).rev().map(|i| Coord { lat: 42.0 + i as f64 * 0.00045, lon: i as f64 * 0.0006 }));
+    assert_eq!(analyse_shape(&loop_coords).shape, TrackShape::Loop);
+
+    assert_eq!(analyse_shape(&[]).shape, TrackShape::Unknown);
+    assert_eq!(analyse_shape(&test_line(0, 1)).shape, TrackShape::Unknown);
+}
+
+#[test]
+fn test_find_self_overlaps() {
+    let out_and_back: Vec<Coord> = test_line(0, 40).into_iter().chain(test_line(0, 40).into_iter().rev()).collect();
+
+    // The way back (40..80) is within 10m of the way out, except around the turnaround
+    let overlaps = find_self_overlaps(&out_and_back, COMMON_COORDS_THRESHOLD, NEIGHBOUR_EXCLUSION);
+    assert_eq!(overlaps, vec![SelfOverlap { first: (0, 38), second: (41, 79) }]);
+
+    assert!(find_self_overlaps(&test_line(0, 40), COMMON_COORDS_THRESHOLD, NEIGHBOUR_EXCLUSION).is_empty());
+}
+
+// END FUNCTIONS =======================================================================================  END FUNCTIONS
+
+//
+// * End of file analysis_utils.rs
+//
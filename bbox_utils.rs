@@ -0,0 +1,459 @@
+///
+/// # bbox_utils.rs
+/// This file contains the bounding boxes of the tracks, their index file
+/// and the region queries over the trail catalogue.
+///
+/// ## Author
+/// * Tom Planche - <github.com/tomPlanche>
+
+// IMPORTS ===================================================================================================  IMPORTS
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs::read_to_string;
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+use crate::file_utils::{get_output_file_path, hash_file, read_file_name, read_gpx_file, save_serializable_to_json};
+use crate::gpx_utils::{calc_distance, Coord, DistanceUnit};
+// END IMPORTS ==========================================================================================   END IMPORTS
+
+// VARIABLES ================================================================================================ VARIABLE
+// Name of the index file in the 'output' folder.
+#[allow(dead_code)]
+pub const BBOX_INDEX_FILE: &str = "bbox_index.json";
+
+// Length (in meters) of a degree of latitude, to expand the boxes.
+const METERS_PER_DEGREE: f64 = 111_195.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct BoundingBox {
+    pub min_lat: f64,
+    pub min_lon: f64,
+    pub max_lat: f64,
+    pub max_lon: f64,
+}
+
+// Bounding box of a track, with the hash of the file it was computed from
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct IndexedBbox {
+    pub hash: String,
+    pub bbox: BoundingBox,
+}
+
+// Map of file names to the bounding box of their track
+#[allow(dead_code)]
+pub type BboxIndex = BTreeMap<String, IndexedBbox>;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum RegionQuery {
+    Bbox(BoundingBox),
+    Near { coord: Coord, radius: f64 },
+}
+
+// Trail matching a query, with the inclusive index ranges of its matching points
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TrailMatch {
+    pub file: String,
+    pub ranges: Vec<(usize, usize)>,
+}
+// END VARIABLES =======================================================================================  END VARIABLES
+
+// FUNCTIONS ================================================================================================ FUNCTIONS
+impl BoundingBox {
+    ///
+    /// # from_coords
+    /// Compute the bounding box of coordinates.
+    ///
+    /// ## Arguments
+    /// * `coords` - The coordinates.
+    ///
+    /// ## Returns
+    /// * `Option<BoundingBox>` - The bounding box, None without coordinates.
+    #[allow(dead_code)]
+    pub fn from_coords(coords: &[Coord]) -> Option<BoundingBox> {
+        let first = coords.first()?;
+
+        Some(coords.iter().fold(
+            BoundingBox {
+                min_lat: first.lat,
+                min_lon: first.lon,
+                max_lat: first.lat,
+                max_lon: first.lon,
+            },
+            |bbox, coord| BoundingBox {
+                min_lat: bbox.min_lat.min(coord.lat),
+                min_lon: bbox.min_lon.min(coord.lon),
+                max_lat: bbox.max_lat.max(coord.lat),
+                max_lon: bbox.max_lon.max(coord.lon),
+            },
+        ))
+    }
+
+    ///
+    /// # contains
+    /// Check if a coordinate is inside the box, edges included.
+    #[allow(dead_code)]
+    pub fn contains(&self, coord: Coord) -> bool {
+        (self.min_lat..=self.max_lat).contains(&coord.lat) && (self.min_lon..=self.max_lon).contains(&coord.lon)
+    }
+
+    ///
+    /// # intersects
+    /// Check if two boxes share at least a point.
+    #[allow(dead_code)]
+    pub fn intersects(&self, other: &BoundingBox) -> bool {
+        self.min_lat <= other.max_lat
+            && other.min_lat <= self.max_lat
+            && self.min_lon <= other.max_lon
+            && other.min_lon <= self.max_lon
+    }
+
+    ///
+    /// # intersects_segment
+    /// Check if a segment, as a straight line between the degrees of its ends, crosses the box.
+    /// Clipped with the Liang-Barsky algorithm.
+    ///
+    /// ## Arguments
+    /// * `start` - The start of the segment.
+    /// * `end` - The end of the segment.
+    ///
+    /// ## Returns
+    /// * `bool` - True if a point of the segment is inside the box, edges included.
+    #[allow(dead_code)]
+    pub fn intersects_segment(&self, start: Coord, end: Coord) -> bool {
+        let delta_lat: f64 = end.lat - start.lat;
+        let delta_lon: f64 = end.lon - start.lon;
+        // Fractions of the segment between which it is inside the box
+        let (mut enter, mut exit): (f64, f64) = (0.0, 1.0);
+
+        for (delta, distance) in [
+            (-delta_lat, start.lat - self.min_lat),
+            (delta_lat, self.max_lat - start.lat),
+            (-delta_lon, start.lon - self.min_lon),
+            (delta_lon, self.max_lon - start.lon),
+        ] {
+            if delta == 0.0 {
+                // Parallel to this edge, outside of it
+                if distance < 0.0 {
+                    return false;
+                }
+                continue;
+            }
+
+            let fraction: f64 = distance / delta;
+            if delta < 0.0 {
+                enter = enter.max(fraction);
+            } else {
+                exit = exit.min(fraction);
+            }
+
+            if enter > exit {
+                return false;
+            }
+        }
+
+        true
+    }
+
+    ///
+    /// # expand
+    /// Grow the box by a distance on each side.
+    ///
+    /// ## Arguments
+    /// * `meters` - The distance (in meters).
+    ///
+    /// ## Returns
+    /// * `BoundingBox` - The expanded box, containing every point within `meters` of the box.
+    #[allow(dead_code)]
+    pub fn expand(&self, meters: f64) -> BoundingBox {
+        let delta_lat: f64 = meters / METERS_PER_DEGREE;
+        // The degrees of longitude are the shortest on the side closest to a pole
+        let max_abs_lat: f64 = (self.max_lat.abs().max(self.min_lat.abs()) + delta_lat).min(89.0);
+        let delta_lon: f64 = delta_lat / max_abs_lat.to_radians().cos();
+
+        BoundingBox {
+            min_lat: self.min_lat - delta_lat,
+            min_lon: self.min_lon - delta_lon,
+            max_lat: self.max_lat + delta_lat,
+            max_lon: self.max_lon + delta_lon,
+        }
+    }
+}
+
+impl RegionQuery {
+    ///
+    /// # bbox
+    /// The box containing every point matching the query, to skip the tracks from the index.
+    #[allow(dead_code)]
+    pub fn bbox(&self) -> BoundingBox {
+        match self {
+            RegionQuery::Bbox(bbox) => *bbox,
+            RegionQuery::Near { coord, radius } => BoundingBox::from_coords(&[*coord]).unwrap().expand(*radius),
+        }
+    }
+
+    ///
+    /// # matches
+    /// Check if a coordinate matches the query.
+    #[allow(dead_code)]
+    pub fn matches(&self, coord: Coord) -> bool {
+        match self {
+            RegionQuery::Bbox(bbox) => bbox.contains(coord),
+            RegionQuery::Near { coord: center, radius } => calc_distance(*center, coord, DistanceUnit::Meters) <= *radius,
+        }
+    }
+
+    ///
+    /// # matches_segment
+    /// Check if a segment between two coordinates matches the query,
+    /// a long segment crossing the box or passing by the point matching even if its ends do not.
+    #[allow(dead_code)]
+    pub fn matches_segment(&self, start: Coord, end: Coord) -> bool {
+        match self {
+            RegionQuery::Bbox(bbox) => bbox.intersects_segment(start, end),
+            RegionQuery::Near { coord: center, radius } => {
+                self.matches(start) || self.matches(end) || distance_to_segment(*center, start, end) <= *radius
+            }
+        }
+    }
+}
+
+///
+/// # distance_to_segment
+/// Calculate the distance from a coordinate to the closest point of a segment.
+/// The segment is projected on the plane tangent at the coordinate, close enough for the length of a track segment.
+///
+/// ## Arguments
+/// * `coord` - The coordinate.
+/// * `start` - The start of the segment.
+/// * `end` - The end of the segment.
+///
+/// ## Returns
+/// * `f64` - The distance (in meters).
+#[allow(dead_code)]
+pub fn distance_to_segment(coord: Coord, start: Coord, end: Coord) -> f64 {
+    let meters_per_degree_lon: f64 = METERS_PER_DEGREE * coord.lat.to_radians().cos();
+    let project = |other: Coord| ((other.lon - coord.lon) * meters_per_degree_lon, (other.lat - coord.lat) * METERS_PER_DEGREE);
+
+    let (start_x, start_y) = project(start);
+    let (end_x, end_y) = project(end);
+    let (delta_x, delta_y) = (end_x - start_x, end_y - start_y);
+    let length_squared: f64 = delta_x * delta_x + delta_y * delta_y;
+
+    // Fraction of the segment of the closest point, the coordinate being the origin
+    let fraction: f64 = if length_squared == 0.0 {
+        0.0
+    } else {
+        (-(start_x * delta_x + start_y * delta_y) / length_squared).clamp(0.0, 1.0)
+    };
+
+    (start_x + fraction * delta_x).hypot(start_y + fraction * delta_y)
+}
+
+///
+/// # find_index_ranges
+/// Find the ranges of consecutive coordinates matching a query,
+/// both ends of a segment matching the query being in the range.
+///
+/// ## Arguments
+/// * `coords` - The coordinates of the track.
+/// * `query` - The query.
+///
+/// ## Returns
+/// * `Vec<(usize, usize)>` - The inclusive index ranges.
+#[allow(dead_code)]
+pub fn find_index_ranges(coords: &[Coord], query: &RegionQuery) -> Vec<(usize, usize)> {
+    let mut ranges: Vec<(usize, usize)> = Vec::new();
+    let mut matching: Vec<bool> = coords.iter().map(|coord| query.matches(*coord)).collect();
+
+    for index in 1..coords.len() {
+        if !(matching[index - 1] && matching[index]) && query.matches_segment(coords[index - 1], coords[index]) {
+            matching[index - 1] = true;
+            matching[index] = true;
+        }
+    }
+
+    for (index, _) in matching.iter().enumerate().filter(|(_, matches)| **matches) {
+        match ranges.last_mut() {
+            Some((_, end)) if *end + 1 == index => *end = index,
+            _ => ranges.push((index, index)),
+        }
+    }
+
+    ranges
+}
+
+///
+/// # update_bbox_index
+/// Compute the boxes of the files missing from an index or modified since,
+/// and drop the files not given.
+///
+/// ## Arguments
+/// * `index` - The index, updated.
+/// * `gpx_files` - The GPX files to index.
+///
+/// ## Returns
+/// * `bool` - True if the index changed, false otherwise.
+#[allow(dead_code)]
+pub fn update_bbox_index(index: &mut BboxIndex, gpx_files: &[PathBuf]) -> bool {
+    let mut updated: bool = false;
+    let mut file_names: BTreeSet<String> = BTreeSet::new();
+
+    for file in gpx_files {
+        let file_name: String = match read_file_name(file) {
+            Some(file_name) => file_name,
+            None => panic!("Could not read the file name of {:?}", file),
+        };
+        file_names.insert(file_name.clone());
+
+        let hash: String = match hash_file(file) {
+            Some(hash) => hash,
+            None => panic!("Could not read the file {:?}", file),
+        };
+
+        if index.get(&file_name).is_some_and(|entry| entry.hash == hash) {
+            continue;
+        }
+
+        let coords: Vec<Coord> = match read_gpx_file(file) {
+            Some(coords) => coords,
+            None => panic!("Could not read the file {:?}", file),
+        };
+
+        match BoundingBox::from_coords(&coords) {
+            Some(bbox) => index.insert(file_name, IndexedBbox { hash, bbox }),
+            None => index.remove(&file_name),
+        };
+        updated = true;
+    }
+
+    // The files removed from the folder
+    let indexed: usize = index.len();
+    index.retain(|file_name, _| file_names.contains(file_name));
+
+    updated || index.len() != indexed
+}
+
+///
+/// # load_bbox_index
+/// Load the bounding box index of the 'output' folder,
+/// updating and saving it when files were added, modified or removed.
+///
+/// ## Arguments
+/// * `gpx_files` - The GPX files to index.
+///
+/// ## Returns
+/// * `BboxIndex` - The index of the files.
+#[allow(dead_code)]
+pub fn load_bbox_index(gpx_files: &[PathBuf]) -> BboxIndex {
+    let index_path: PathBuf = get_output_file_path(BBOX_INDEX_FILE);
+
+    // An unreadable index, or one saved without the hashes, is computed again
+    let mut index: BboxIndex = read_to_string(&index_path)
+        .ok()
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default();
+
+    if update_bbox_index(&mut index, gpx_files) {
+        save_serializable_to_json(&index, &index_path);
+    }
+
+    index
+}
+
+#[test]
+fn test_bounding_box() {
+    let coords = vec![Coord { lat: 42.7, lon: 0.1 }, Coord { lat: 42.6, lon: 0.2 }];
+    let bbox = BoundingBox::from_coords(&coords).unwrap();
+
+    assert_eq!(bbox, BoundingBox { min_lat: 42.6, min_lon: 0.1, max_lat: 42.7, max_lon: 0.2 });
+    assert!(bbox.intersects(&BoundingBox { min_lat: 42.65, min_lon: 0.15, max_lat: 43.0, max_lon: 1.0 }));
+    assert!(!bbox.intersects(&BoundingBox { min_lat: 42.8, min_lon: 0.1, max_lat: 43.0, max_lon: 0.2 }));
+
+    // 1km around a point contains the points 1km away in every direction
+    let center = Coord { lat: 42.68, lon: 0.08 };
+    let expanded = BoundingBox::from_coords(&[center]).unwrap().expand(1000.0);
+    assert!(expanded.contains(crate::gpx_utils::calc_destination(center, 90.0, 999.0)));
+    assert!(expanded.contains(crate::gpx_utils::calc_destination(center, 180.0, 999.0)));
+}
+
+#[test]
+fn test_find_index_ranges() {
+    let coords: Vec<Coord> = (0..6).map(|i| Coord { lat: 42.0 + i as f64 * 0.001, lon: 0.0 }).collect();
+    let query = RegionQuery::Near { coord: Coord { lat: 42.0025, lon: 0.0 }, radius: 200.0 };
+
+    // The points 1 to 4 are within 200m, the segments leaving them too
+    assert_eq!(find_index_ranges(&coords, &query), vec![(0, 5)]);
+
+    let bbox = BoundingBox { min_lat: 41.0, min_lon: -1.0, max_lat: 42.0015, max_lon: 1.0 };
+    assert_eq!(find_index_ranges(&coords, &RegionQuery::Bbox(bbox)), vec![(0, 2)]);
+}
+
+#[test]
+fn test_find_index_ranges_segments() {
+    // A single long segment, 1.1km going north then 1.1km going east
+    let coords = vec![Coord { lat: 42.0, lon: 0.0 }, Coord { lat: 42.01, lon: 0.0 }, Coord { lat: 42.01, lon: 0.0134 }];
+
+    // A box crossed by the first segment, none of the points inside
+    let bbox = BoundingBox { min_lat: 42.004, min_lon: -0.001, max_lat: 42.006, max_lon: 0.001 };
+    assert_eq!(find_index_ranges(&coords, &RegionQuery::Bbox(bbox)), vec![(0, 1)]);
+
+    // A box next to the segments, whose own box contains it
+    let bbox = BoundingBox { min_lat: 42.004, min_lon: 0.004, max_lat: 42.006, max_lon: 0.006 };
+    assert!(find_index_ranges(&coords, &RegionQuery::Bbox(bbox)).is_empty());
+
+    // 50m east of the middle of the first segment, 550m from both of its ends
+    let center = Coord { lat: 42.005, lon: 0.0006 };
+    assert!((distance_to_segment(center, coords[0], coords[1]) - 50.0).abs() < 1.0);
+    assert_eq!(find_index_ranges(&coords, &RegionQuery::Near { coord: center, radius: 100.0 }), vec![(0, 1)]);
+    assert!(find_index_ranges(&coords, &RegionQuery::Near { coord: center, radius: 40.0 }).is_empty());
+
+    // Past the end of the segment, the distance is the one to the end
+    let beyond = Coord { lat: 42.02, lon: 0.0 };
+    assert!((distance_to_segment(beyond, coords[0], coords[1]) - calc_distance(beyond, coords[1], DistanceUnit::Meters)).abs() < 1.0);
+}
+
+#[test]
+fn test_update_bbox_index() {
+    use std::fs::{create_dir_all, remove_dir_all};
+
+    use crate::file_utils::write_gpx_file;
+    use crate::gpx_utils::Point;
+
+    // The process id keeps parallel runs apart
+    let folder = std::env::temp_dir().join(format!("bbox_utils_index_test_{}", std::process::id()));
+    let _ = remove_dir_all(&folder);
+    create_dir_all(&folder).unwrap();
+
+    let file = folder.join("a.gpx");
+    let write = |lat: f64| {
+        let points: Vec<Point> = [lat, lat + 0.01]
+            .iter()
+            .map(|lat| Point { coords: Coord { lat: *lat, lon: 0.1 }, name: None, description: None, elevation: None })
+            .collect();
+        write_gpx_file(&points, "a", None, &file);
+    };
+
+    let files = vec![file.clone()];
+    let mut index = BboxIndex::new();
+    write(42.0);
+    assert!(update_bbox_index(&mut index, &files));
+    assert!(!update_bbox_index(&mut index, &files));
+
+    // An edited file gets its new box
+    write(43.0);
+    assert!(update_bbox_index(&mut index, &files));
+    assert_eq!(index["a.gpx"].bbox.min_lat, 43.0);
+
+    // A removed file is dropped
+    assert!(update_bbox_index(&mut index, &[]));
+    assert!(index.is_empty());
+
+    remove_dir_all(&folder).unwrap();
+}
+
+// END FUNCTIONS =======================================================================================  END FUNCTIONS
+
+//
+// * End of file bbox_utils.rs
+//
@@ -1,6 +1,11 @@
 /// # calc_distance_from_json_point.rs
 /// This file will be a binary.
-/// It'll recieve a json for two Coordd and calculate the distance between them.
+/// It'll recieve a json for two Coordd and calculate the distance between them (in meters).
+///
+/// Arguments:
+/// * coord_1 and coord_2 - Two jsons representing two Coord.
+/// * model (optional) - `haversine` (default), `vincenty`, `equirectangular`,
+///   or a projection of `projection_utils` to measure in its plane.
 ///
 /// ## Author
 /// * Tom Planche - <github.com/tomPlanche>
@@ -13,7 +18,13 @@
 #[path = "gpx_utils.rs"]
 mod gpx_utils;
 
-use crate::gpx_utils::{Coord, calc_distance};
+#[path = "projection_utils.rs"]
+mod projection_utils;
+
+use ansi_term::Colour::Yellow;
+
+use crate::gpx_utils::{Coord, calc_distance_with, DistanceModel, DistanceUnit};
+use crate::projection_utils::{calc_planar_distance, Projection};
 // END IMPORTS ==========================================================================================   END IMPORTS
 
 // VARIABLES ================================================================================================ VARIABLE
@@ -26,8 +37,8 @@ use crate::gpx_utils::{Coord, calc_distance};
 fn main() {
     let args: Vec<String> = std::env::args().collect();
 
-    if args.len() != 3 {
-        panic!("This binary takes two arguments: two jsons representing two Coord");
+    if args.len() < 3 || args.len() > 4 {
+        panic!("This binary takes two jsons representing two Coord, then optionally a distance model");
     }
 
     let coord_1: Coord = match serde_json::from_str(&args[1]) {
@@ -40,7 +51,27 @@ fn main() {
         Err(_) => panic!("Could not parse the second argument as a Coord"),
     };
 
-    println!("{}", calc_distance(coord_1, coord_2, Some(true)));
+    let model: DistanceModel = match args.get(3) {
+        Some(model) => match model.parse() {
+            Ok(model) => model,
+            Err(message) => match model.parse::<Projection>() {
+                Ok(projection) => {
+                    println!("{}", calc_planar_distance(coord_1, coord_2, projection));
+                    return;
+                }
+                Err(_) => panic!("{}", message),
+            },
+        },
+        None => DistanceModel::default(),
+    };
+
+    let (distance, used_model): (f64, DistanceModel) = calc_distance_with(coord_1, coord_2, model, DistanceUnit::Meters);
+    if used_model != model {
+        // On stderr, to keep the distance alone on stdout
+        eprintln!("{}", Yellow.paint(format!("{:?} did not converge, {:?} was used", model, used_model)));
+    }
+
+    println!("{}", distance);
 }
 
 // END CODE =======================================================================================  END COMPONENT
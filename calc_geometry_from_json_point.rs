@@ -0,0 +1,100 @@
+/// # calc_geometry_from_json_point.rs
+/// This file will be a binary.
+/// It'll recieve jsons of Coord and compute the great circle geometry used by the markers:
+/// bearings, destination, midpoint and interpolation.
+///
+/// Arguments:
+/// * `bearing <coord_1> <coord_2>` - The initial and final bearings (in degrees).
+/// * `destination <coord> <bearing> <distance>` - The Coord reached after `distance` meters.
+/// * `midpoint <coord_1> <coord_2>` - The midpoint Coord.
+/// * `interpolate <coord_1> <coord_2> <fraction>` - The Coord at `fraction` (0 to 1) of the way.
+///
+/// ## Author
+/// * Tom Planche - <github.com/tomPlanche>
+///
+/// ## Example
+/// >> cargo run --bin calc_geometry_from_json_point midpoint "{\"lat\": 0.0, \"lon\": 0.0}" "{\"lat\": 0.0, \"lon\": 10.0}"
+/// {"lat":0.0,"lon":5.0}
+
+// IMPORTS ===================================================================================================  IMPORTS
+#[path = "gpx_utils.rs"]
+mod gpx_utils;
+
+use crate::gpx_utils::{
+    calc_destination,
+    calc_final_bearing,
+    calc_initial_bearing,
+    calc_midpoint,
+    interpolate_coords,
+    Coord,
+};
+// END IMPORTS ==========================================================================================   END IMPORTS
+
+// FUNCTIONS ================================================================================================ FUNCTIONS
+///
+/// # parse_coord
+/// Parse an argument as a Coord.
+fn parse_coord(arg: &str, position: &str) -> Coord {
+    match serde_json::from_str(arg) {
+        Ok(coord) => coord,
+        Err(_) => panic!("Could not parse the {} argument as a Coord", position),
+    }
+}
+
+///
+/// # parse_number
+/// Parse an argument as a number.
+fn parse_number(arg: &str, name: &str) -> f64 {
+    match arg.parse() {
+        Ok(number) => number,
+        Err(_) => panic!("Could not parse the {} as a number", name),
+    }
+}
+// END FUNCTIONS =======================================================================================  END FUNCTIONS
+
+// CODE ========================================================================================================= CODE
+fn main() {
+    let args: Vec<String> = std::env::args().collect();
+
+    if args.len() < 4 {
+        panic!("This binary takes an operation (bearing, destination, midpoint or interpolate) and its arguments");
+    }
+
+    let coord_1: Coord = parse_coord(&args[2], "first");
+
+    match (args[1].as_str(), args.len()) {
+        ("bearing", 4) => {
+            let coord_2: Coord = parse_coord(&args[3], "second");
+
+            println!("initial: {}", calc_initial_bearing(coord_1, coord_2));
+            println!("final: {}", calc_final_bearing(coord_1, coord_2));
+        }
+        ("destination", 5) => {
+            let destination: Coord = calc_destination(
+                coord_1,
+                parse_number(&args[3], "bearing"),
+                parse_number(&args[4], "distance"),
+            );
+
+            println!("{}", serde_json::to_string(&destination).unwrap());
+        }
+        ("midpoint", 4) => {
+            let coord_2: Coord = parse_coord(&args[3], "second");
+
+            println!("{}", serde_json::to_string(&calc_midpoint(coord_1, coord_2)).unwrap());
+        }
+        ("interpolate", 5) => {
+            let coord_2: Coord = parse_coord(&args[3], "second");
+            let fraction: f64 = parse_number(&args[4], "fraction");
+
+            println!("{}", serde_json::to_string(&interpolate_coords(coord_1, coord_2, fraction)).unwrap());
+        }
+        (operation, _) => panic!("Unknown operation {:?} or wrong number of arguments", operation),
+    }
+}
+
+// END CODE =======================================================================================  END COMPONENT
+
+//
+// * End of file /calc_geometry_from_json_point.rs
+//
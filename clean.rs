@@ -0,0 +1,92 @@
+/// # clean.rs
+/// This binary removes the GPS noise of a GPX file of the 'assets' folder:
+/// spikes, stationary clusters and, optionally, jitter with a moving average.
+///
+/// Arguments:
+/// * file - The name of the GPX file in the 'assets' folder.
+/// * window (optional) - The size of the moving-average window, no smoothing by default.
+///
+/// The cleaned track and the report of what was removed are saved in the 'output' folder.
+///
+/// ## Author
+/// * Tom Planche - <github.com/tomPlanche>
+///
+/// ## Example
+/// >> cargo run --bin clean EspadaIbonetCircuito.gpx 5
+/// Successfully saved to: output/EspadaIbonetCircuito_clean.gpx
+/// Successfully saved to: output/EspadaIbonetCircuito_clean_report.json
+
+// IMPORTS ===================================================================================================  IMPORTS
+#[path = "gpx_utils.rs"]
+mod gpx_utils;
+
+#[path = "file_utils.rs"]
+mod file_utils;
+
+#[path = "utils.rs"]
+mod utils;
+
+#[path = "clean_utils.rs"]
+mod clean_utils;
+
+use std::path::PathBuf;
+
+use crate::clean_utils::{clean_track, CleanOptions, CleanReport};
+use crate::file_utils::{
+    file_name_to_path_buf,
+    file_stem,
+    get_output_file_path,
+    read_gpx_points,
+    save_serializable_to_json,
+    write_gpx_file,
+};
+use crate::gpx_utils::Point;
+// END IMPORTS ==========================================================================================   END IMPORTS
+
+// CODE ========================================================================================================= CODE
+fn main() {
+    let args: Vec<String> = std::env::args().collect();
+
+    if args.len() < 2 || args.len() > 3 {
+        panic!("This binary takes a GPX file name and optionally a smoothing window");
+    }
+
+    let file_name: &str = &args[1];
+    let options = CleanOptions {
+        smoothing_window: match args.get(2) {
+            Some(window) => window.parse().expect("The smoothing window must be a number"),
+            None => 0,
+        },
+        ..Default::default()
+    };
+
+    let path_buff_from_file: PathBuf = file_name_to_path_buf(file_name);
+    let points: Vec<Point> = match read_gpx_points(&path_buff_from_file) {
+        Some(points) => points,
+        None => panic!("Could not read the file {:?}", file_name),
+    };
+
+    let (cleaned, report): (Vec<Point>, CleanReport) = clean_track(&points, &options);
+
+    println!(
+        "{} spikes and {} stationary points removed, {} points left",
+        report.removed_spikes.len(),
+        report.removed_stationary.len(),
+        report.points_after
+    );
+
+    let file_stem: &str = file_stem(file_name);
+
+    write_gpx_file(
+        &cleaned,
+        file_stem,
+        Some(format!("Cleaned from {}", file_name)),
+        &get_output_file_path(&format!("{}_clean.gpx", file_stem)),
+    );
+    save_serializable_to_json(&report, &get_output_file_path(&format!("{}_clean_report.json", file_stem)));
+}
+// END CODE =======================================================================================  END COMPONENT
+
+//
+// * End of file /clean.rs
+//
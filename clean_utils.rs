@@ -0,0 +1,242 @@
+///
+/// # clean_utils.rs
+/// This file contains the functions used to clean a recorded track:
+/// spike removal, stationary points de-duplication and moving-average smoothing.
+///
+/// ## Author
+/// * Tom Planche - <github.com/tomPlanche>
+
+// IMPORTS ===================================================================================================  IMPORTS
+use serde::{Deserialize, Serialize};
+
+use crate::gpx_utils::{calc_distance, Coord, DistanceUnit, Point};
+// END IMPORTS ==========================================================================================   END IMPORTS
+
+// VARIABLES ================================================================================================ VARIABLE
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct CleanOptions {
+    // A point further than this (in meters) from both its neighbours,
+    // while they are close to each other, is a spike.
+    pub spike_distance: f64,
+    // Consecutive points closer than this (in meters) are a stationary cluster.
+    pub stationary_distance: f64,
+    // Size of the moving-average window, no smoothing if lower than 2.
+    pub smoothing_window: usize,
+}
+
+impl Default for CleanOptions {
+    fn default() -> Self {
+        CleanOptions {
+            spike_distance: 100.0,
+            stationary_distance: 2.0,
+            smoothing_window: 0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CleanReport {
+    // Indexes in the original track
+    pub removed_spikes: Vec<usize>,
+    pub removed_stationary: Vec<usize>,
+    pub smoothing_window: usize,
+    pub points_before: usize,
+    pub points_after: usize,
+}
+// END VARIABLES =======================================================================================  END VARIABLES
+
+// FUNCTIONS ================================================================================================ FUNCTIONS
+///
+/// # find_spikes
+/// Find the points far from both their neighbours while the neighbours are close to each other.
+///
+/// ## Arguments
+/// * `points` - The points of the track.
+/// * `spike_distance` - The distance from the neighbours (in meters) above which a point is a spike.
+///
+/// ## Returns
+/// * `Vec<usize>` - The indexes of the spikes.
+#[allow(dead_code)]
+pub fn find_spikes(points: &[Point], spike_distance: f64) -> Vec<usize> {
+    let mut spikes: Vec<usize> = Vec::new();
+
+    for index in 1..points.len().saturating_sub(1) {
+        let previous = points[index - 1].coords;
+        let current = points[index].coords;
+        let next = points[index + 1].coords;
+
+        if calc_distance(previous, current, DistanceUnit::Meters) > spike_distance
+            && calc_distance(current, next, DistanceUnit::Meters) > spike_distance
+            && calc_distance(previous, next, DistanceUnit::Meters) < spike_distance
+        {
+            spikes.push(index);
+        }
+    }
+
+    spikes
+}
+
+///
+/// # find_stationary_points
+/// Find the points closer than `stationary_distance` to the last kept point.
+/// The first point of each cluster is kept.
+///
+/// ## Arguments
+/// * `points` - The points of the track.
+/// * `stationary_distance` - The distance (in meters) under which points are merged.
+///
+/// ## Returns
+/// * `Vec<usize>` - The indexes of the points to remove.
+#[allow(dead_code)]
+pub fn find_stationary_points(points: &[Point], stationary_distance: f64) -> Vec<usize> {
+    let mut stationary: Vec<usize> = Vec::new();
+    let mut last_kept: Option<Coord> = None;
+
+    for (index, point) in points.iter().enumerate() {
+        match last_kept {
+            Some(kept) if calc_distance(kept, point.coords, DistanceUnit::Meters) < stationary_distance => {
+                stationary.push(index);
+            }
+            _ => last_kept = Some(point.coords),
+        }
+    }
+
+    stationary
+}
+
+///
+/// # moving_average
+/// Smooth the latitude, longitude and elevation with a centered moving average.
+/// The window is shortened at both ends of the track.
+///
+/// ## Arguments
+/// * `points` - The points of the track.
+/// * `window` - The number of points of the window.
+///
+/// ## Returns
+/// * `Vec<Point>` - The smoothed points.
+#[allow(dead_code)]
+pub fn moving_average(points: &[Point], window: usize) -> Vec<Point> {
+    if window < 2 {
+        return points.to_vec();
+    }
+
+    let half = window / 2;
+
+    (0..points.len())
+        .map(|index| {
+            let neighbours = &points[index.saturating_sub(half)..(index + half + 1).min(points.len())];
+            let count = neighbours.len() as f64;
+
+            let elevations: Vec<f64> = neighbours.iter().filter_map(|point| point.elevation).collect();
+
+            Point {
+                coords: Coord {
+                    lat: neighbours.iter().map(|point| point.coords.lat).sum::<f64>() / count,
+                    lon: neighbours.iter().map(|point| point.coords.lon).sum::<f64>() / count,
+                },
+                elevation: points[index]
+                    .elevation
+                    .map(|_| elevations.iter().sum::<f64>() / elevations.len() as f64),
+                ..points[index].clone()
+            }
+        })
+        .collect()
+}
+
+///
+/// # remove_positions
+/// Remove the points at the given positions, returning the original indexes of the removed points.
+fn remove_positions(indexed: &mut Vec<(usize, Point)>, positions: &[usize]) -> Vec<usize> {
+    let removed: Vec<usize> = positions.iter().map(|position| indexed[*position].0).collect();
+
+    let mut position: usize = 0;
+    indexed.retain(|_| {
+        position += 1;
+        !positions.contains(&(position - 1))
+    });
+
+    removed
+}
+
+///
+/// # clean_track
+/// Run the whole cleaning pipeline on a track.
+///
+/// ## Arguments
+/// * `points` - The points of the track.
+/// * `options` - The thresholds of each stage.
+///
+/// ## Returns
+/// * `(Vec<Point>, CleanReport)` - The cleaned points and what was removed.
+#[allow(dead_code)]
+pub fn clean_track(points: &[Point], options: &CleanOptions) -> (Vec<Point>, CleanReport) {
+    // Keep the original indexes through the stages for the report
+    let mut indexed: Vec<(usize, Point)> = points.iter().cloned().enumerate().collect();
+    let current = |indexed: &Vec<(usize, Point)>| -> Vec<Point> {
+        indexed.iter().map(|(_, point)| point.clone()).collect()
+    };
+
+    let spikes = find_spikes(&current(&indexed), options.spike_distance);
+    let removed_spikes = remove_positions(&mut indexed, &spikes);
+
+    let stationary = find_stationary_points(&current(&indexed), options.stationary_distance);
+    let removed_stationary = remove_positions(&mut indexed, &stationary);
+
+    let cleaned = moving_average(&current(&indexed), options.smoothing_window);
+
+    let report = CleanReport {
+        removed_spikes,
+        removed_stationary,
+        smoothing_window: options.smoothing_window,
+        points_before: points.len(),
+        points_after: cleaned.len(),
+    };
+
+    (cleaned, report)
+}
+
+#[cfg(test)]
+fn test_point(lat: f64, lon: f64) -> Point {
+    Point {
+        coords: Coord { lat, lon },
+        name: None,
+        description: None,
+        elevation: None,
+    }
+}
+
+#[test]
+fn test_clean_track() {
+    let points = vec![
+        test_point(42.0, 0.0),
+        test_point(42.0001, 0.0),
+        test_point(42.01, 0.0), // spike, ~1km away
+        test_point(42.0002, 0.0),
+        test_point(42.000201, 0.0), // stationary
+        test_point(42.0003, 0.0),
+    ];
+
+    let (cleaned, report) = clean_track(&points, &CleanOptions::default());
+
+    assert_eq!(report.removed_spikes, vec![2]);
+    assert_eq!(report.removed_stationary, vec![4]);
+    assert_eq!(cleaned.len(), 4);
+    assert_eq!(report.points_after, 4);
+}
+
+#[test]
+fn test_moving_average() {
+    let points = vec![test_point(42.0, 0.0), test_point(42.3, 0.0), test_point(42.0, 0.0)];
+    let smoothed = moving_average(&points, 3);
+
+    assert!((smoothed[0].coords.lat - 42.15).abs() < 1e-9);
+    assert!((smoothed[1].coords.lat - 42.1).abs() < 1e-9);
+    assert!((smoothed[2].coords.lat - 42.15).abs() < 1e-9);
+}
+
+// END FUNCTIONS =======================================================================================  END FUNCTIONS
+
+//
+// * End of file clean_utils.rs
+//
@@ -17,6 +17,24 @@
 /// }
 /// ```
 ///
+/// With the `--self` argument, each file is compared with itself instead
+/// and the sections passing within `--radius <m>` (10m by default) of their own earlier points,
+/// at least `--gap <m>` (50m by default) further along the track, are saved in 'self_overlaps.json':
+/// ```json
+/// {
+///   "file_1": [{ "first": [start, end], "second": [start, end] }, ...],
+///   ...
+/// }
+/// ```
+///
+/// With the `--update <files>` argument, only the given files are compared with the others
+/// and merged in the existing 'final.json' file, the files removed from the 'assets' folder being dropped from it.
+/// With `--incremental`, the updated files are the ones added, modified or removed since the last run,
+/// found with the hashes of the compared files saved in 'final_manifest.json'.
+///
+/// The files are read and the pairs compared on every CPU core, or on the number of threads given by `--jobs <n>`.
+/// The output does not depend on the order the pairs finish in.
+///
 /// ## Author
 /// * Tom Planche - <github.com/tomPlanche>
 
@@ -30,97 +48,292 @@ mod file_utils;
 #[path = "utils.rs"]
 mod utils;
 
-use crate::gpx_utils::{calc_distance, Coord};
-use crate::file_utils::{look_4_files, read_file_name, read_gpx_file, save_to_json};
-use crate::utils::{FileCoordsHM, get_unique_pairs};
+#[path = "elevation_utils.rs"]
+mod elevation_utils;
+
+#[path = "analysis_utils.rs"]
+mod analysis_utils;
+
+use crate::gpx_utils::{find_common_indexes, Coord, COMMON_COORDS_THRESHOLD};
+use crate::analysis_utils::{find_self_overlaps, SelfOverlap, NEIGHBOUR_EXCLUSION};
+use crate::file_utils::{
+    get_final_json_path,
+    get_output_file_path,
+    hash_file,
+    load_from_json,
+    load_serializable_from_json,
+    look_4_files,
+    read_file_name,
+    read_gpx_files,
+    save_serializable_to_json,
+    save_to_json,
+    FILE_HASHES_FILE,
+};
+use crate::utils::{FileCoordsHM, FileHashes, find_changed_files, get_unique_pairs, remove_file_from_map};
 
 use std::path::PathBuf;
-use std::collections::{HashMap};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use rayon::prelude::*;
+use rayon::ThreadPoolBuilder;
 // END IMPORTS ==========================================================================================   END IMPORTS
 
 
 // FUNCTIONS ================================================================================================ FUNCTIONS
-fn main() {
-    // Map of file names to Vec<Coords>
-    let mut gpx_coords_map: HashMap<
-        String,
-        Vec<Coord>
-    > = HashMap::new();
+///
+/// # compare_with_themselves
+/// Find the self overlaps of each GPX file and save them to 'self_overlaps.json'.
+///
+/// ## Arguments
+/// * `radius` - The maximum distance between the overlapping points (in meters).
+/// * `min_track_gap` - The distance along the track under which points are neighbours (in meters).
+fn compare_with_themselves(radius: f64, min_track_gap: f64) {
+    let gpx_coords_map: BTreeMap<String, Vec<Coord>> = read_gpx_files(&look_4_files());
 
-    // Final map containing for each pair of files the common coordinates
-    let mut file_coords_map: FileCoordsHM = HashMap::new();
+    let self_overlaps_map: BTreeMap<String, Vec<SelfOverlap>> = gpx_coords_map
+        .par_iter()
+        .map(|(file_name, coords)| {
+            println!("Comparing {:?} with itself", file_name);
 
-    // Get the GPX files
-    let gpx_files: Vec<PathBuf> = look_4_files();
+            (file_name.clone(), find_self_overlaps(coords, radius, min_track_gap))
+        })
+        .filter(|(_, self_overlaps)| !self_overlaps.is_empty())
+        .collect();
 
-    // Init the gpx_coords_map with the actual coordinates
-    for file in &gpx_files {
-        let file_name: String = match read_file_name(file) {
-            Some(file_name) => file_name,
-            None => panic!("Could not read the file name of {:?}", file),
-        };
+    save_serializable_to_json(&self_overlaps_map, &get_output_file_path("self_overlaps.json"));
+
+    println!("Done, look for the 'self_overlaps.json' file in the 'output' folder :)");
+}
+
+///
+/// # compare_pairs
+/// Compare pairs of files in parallel, printing the progress as they finish.
+///
+/// ## Arguments
+/// * `pairs` - The names of the files of each pair.
+/// * `gpx_coords_map` - The coordinates of each file.
+///
+/// ## Returns
+/// * `Vec<Vec<(usize, usize)>>` - The common indexes of each pair, in the order of the pairs.
+fn compare_pairs(pairs: &[(&String, &String)], gpx_coords_map: &BTreeMap<String, Vec<Coord>>) -> Vec<Vec<(usize, usize)>> {
+    let done: AtomicUsize = AtomicUsize::new(0);
+
+    pairs
+        .par_iter()
+        .map(|(file_1_name, file_2_name)| {
+            let common_indexes: Vec<(usize, usize)> = find_common_indexes(
+                &gpx_coords_map[*file_1_name],
+                &gpx_coords_map[*file_2_name],
+                COMMON_COORDS_THRESHOLD,
+            );
+
+            println!(
+                "Compared {:?} and {:?} ({}/{})",
+                file_1_name,
+                file_2_name,
+                done.fetch_add(1, Ordering::Relaxed) + 1,
+                pairs.len()
+            );
+
+            common_indexes
+        })
+        .collect()
+}
+
+///
+/// # hash_gpx_files
+/// Hash the content of the GPX files of the 'assets' folder.
+fn hash_gpx_files() -> FileHashes {
+    look_4_files()
+        .iter()
+        .map(|file| {
+            let file_name: String = match read_file_name(file) {
+                Some(file_name) => file_name,
+                None => panic!("Could not read the file name of {:?}", file),
+            };
+
+            match hash_file(file) {
+                Some(hash) => (file_name, hash),
+                None => panic!("Could not read the file {:?}", file),
+            }
+        })
+        .collect()
+}
+
+///
+/// # load_file_hashes
+/// Load the hashes of the files compared in 'final.json', empty without manifest.
+fn load_file_hashes() -> FileHashes {
+    let manifest_path: PathBuf = get_output_file_path(FILE_HASHES_FILE);
+
+    if manifest_path.exists() {
+        load_serializable_from_json(&manifest_path)
+    } else {
+        FileHashes::new()
+    }
+}
+
+///
+/// # update_files
+/// Compare the given GPX files with the others and merge them in the 'final.json' file,
+/// instead of comparing every pair again.
+/// Their previous common coordinates are dropped first, so a removed file is only dropped.
+///
+/// ## Arguments
+/// * `file_names` - The names of the added, modified or removed files.
+fn update_files(file_names: &[String]) {
+    let mut file_coords_map: FileCoordsHM = if get_final_json_path().exists() {
+        load_from_json(&get_final_json_path())
+    } else {
+        HashMap::new()
+    };
+
+    for file_name in file_names {
+        remove_file_from_map(&mut file_coords_map, file_name);
+    }
+
+    // Map of file names to Vec<Coords>
+    let gpx_coords_map: BTreeMap<String, Vec<Coord>> = read_gpx_files(&look_4_files());
 
-        let coords: Vec<Coord> = match read_gpx_file(file) {
-            Some(coords) => coords,
-            None => panic!("Could not read the file {:?}", file),
+    // The pairs of the updated files with the others, the updated files already paired being skipped
+    let mut pairs: Vec<(&String, &String)> = Vec::new();
+    let mut compared: BTreeSet<&str> = BTreeSet::new();
+
+    for file_name in file_names {
+        let Some((file_name, _)) = gpx_coords_map.get_key_value(file_name) else {
+            println!("{:?} removed", file_name);
+            continue;
         };
 
-        gpx_coords_map.insert(
-            file_name,
-            coords
-        );
+        for other_name in gpx_coords_map.keys() {
+            // In the order of a full run, so 'final.json' does not depend on the updates made
+            if other_name != file_name && !compared.contains(other_name.as_str()) {
+                pairs.push((file_name.min(other_name), file_name.max(other_name)));
+            }
+        }
+
+        compared.insert(file_name);
     }
 
-    // Get the pair of files in order to compare them and avoid repeating
-    let pairs: Vec<(&PathBuf, &PathBuf)> = get_unique_pairs(&gpx_files);
-    for (file_1, file_2) in pairs {
-        println!("Comparing {:?} and {:?}", file_1, file_2);
-
-        // Get the file names
-        let file_1_name: String = match read_file_name(file_1) {
-            Some(file_name) => file_name,
-            None => panic!("Could not read the file name of {:?}", file_1),
+    for ((file_name, other_name), common_indexes) in pairs.iter().zip(compare_pairs(&pairs, &gpx_coords_map)) {
+        if !common_indexes.is_empty() {
+            file_coords_map
+                .entry(file_name.to_string())
+                .or_default()
+                .insert(other_name.to_string(), common_indexes);
+        }
+    }
+
+    save_to_json(file_coords_map);
+
+    // Only the hashes of the updated files, the others may still have to be compared
+    let current_hashes: FileHashes = hash_gpx_files();
+    let mut file_hashes: FileHashes = load_file_hashes();
+    for file_name in file_names {
+        match current_hashes.get(file_name) {
+            Some(hash) => file_hashes.insert(file_name.clone(), hash.clone()),
+            None => file_hashes.remove(file_name),
         };
-        let file_2_name: String = match read_file_name(file_2) {
-            Some(file_name) => file_name,
-            None => panic!("Could not read the file name of {:?}", file_2),
+    }
+
+    save_serializable_to_json(&file_hashes, &get_output_file_path(FILE_HASHES_FILE));
+}
+
+fn main() {
+    let mut args: Vec<String> = std::env::args().collect();
+
+    // The number of threads, every CPU core by default
+    if let Some(position) = args.iter().position(|arg| arg == "--jobs") {
+        let jobs: usize = match args.get(position + 1).map(|jobs| jobs.parse()) {
+            Some(Ok(jobs)) if jobs > 0 => jobs,
+            _ => panic!("Please provide a number of threads after --jobs"),
         };
+        args.drain(position..=position + 1);
+
+        if let Err(error) = ThreadPoolBuilder::new().num_threads(jobs).build_global() {
+            panic!("Could not start the threads: {}", error);
+        }
+    }
+
+    if args.get(1).map(String::as_str) == Some("--update") {
+        let mut file_names: Vec<String> = args[2..].to_vec();
+        file_names.sort();
+        file_names.dedup();
+
+        if file_names.is_empty() {
+            panic!("Please provide the names of the GPX files to update after --update");
+        }
+
+        update_files(&file_names);
+        return;
+    }
 
-        // Get the coordinates
-        let file_1_coords: &Vec<Coord> = gpx_coords_map.get(&*file_1_name).unwrap();
-        let file_2_coords: &Vec<Coord> = gpx_coords_map.get(&*file_2_name).unwrap();
-
-        // Init the file_1_coords_map
-        file_coords_map
-            .entry(file_1_name.clone())
-            .or_default();
-
-        // Compare the coordinates
-        for (index_1, coord_1) in file_1_coords.iter().enumerate() {
-            for (index_2, coord_2) in file_2_coords.iter().enumerate() {
-                if calc_distance(*coord_1, *coord_2, Some(true)) < 10.0 {
-                    // Add the coordinates to the file_coords_map
-                    // since they are only unique pairs, no need to add to file_2
-
-                    // Add the coordinates to the file_1_coords_map
-                    file_coords_map
-                        .get_mut(file_1_name.clone().as_str())
-                        .unwrap()
-                        .entry(file_2_name.clone())
-                        .or_default()
-                        .push((index_1, index_2));
-                }
+    if args.get(1).map(String::as_str) == Some("--incremental") {
+        if get_final_json_path().exists() {
+            let changed_files: Vec<String> = find_changed_files(&load_file_hashes(), &hash_gpx_files());
+
+            if changed_files.is_empty() {
+                println!("The 'final.json' file is up to date");
+            } else {
+                update_files(&changed_files);
             }
+            return;
         }
 
-        // If there are no common coordinates, remove the file_1_name from the file_coords_map
-        if file_coords_map.get(&*file_1_name).unwrap().is_empty() {
-            file_coords_map.remove(&*file_1_name);
+        println!("No 'final.json' file yet, comparing every pair");
+    }
+
+    if args.get(1).map(String::as_str) == Some("--self") {
+        let mut radius: f64 = COMMON_COORDS_THRESHOLD;
+        let mut min_track_gap: f64 = NEIGHBOUR_EXCLUSION;
+
+        let mut rest = args[2..].iter();
+        while let Some(arg) = rest.next() {
+            let value: &mut f64 = match arg.as_str() {
+                "--radius" => &mut radius,
+                "--gap" => &mut min_track_gap,
+                other => panic!("Unknown argument {:?}, expected '--radius <m>' or '--gap <m>'", other),
+            };
+
+            *value = match rest.next().map(|meters| meters.parse::<f64>()) {
+                Some(Ok(meters)) if meters >= 0.0 => meters,
+                _ => panic!("Please provide a distance in meters after {}", arg),
+            };
+        }
+
+        compare_with_themselves(radius, min_track_gap);
+        return;
+    }
+
+    // Final map containing for each pair of files the common coordinates
+    let mut file_coords_map: FileCoordsHM = HashMap::new();
+
+    // Map of file names to Vec<Coords>, sorted by file name
+    let gpx_coords_map: BTreeMap<String, Vec<Coord>> = read_gpx_files(&look_4_files());
+    let file_names: Vec<&String> = gpx_coords_map.keys().collect();
+
+    // Get the pair of files in order to compare them and avoid repeating
+    let pairs: Vec<(&String, &String)> = get_unique_pairs(&file_names)
+        .into_iter()
+        .map(|(file_1_name, file_2_name)| (*file_1_name, *file_2_name))
+        .collect();
+
+    // The results are in the order of the pairs, whatever the order they finish in
+    for ((file_1_name, file_2_name), common_indexes) in pairs.iter().zip(compare_pairs(&pairs, &gpx_coords_map)) {
+        if !common_indexes.is_empty() {
+            // Add the coordinates to the file_coords_map
+            // since they are only unique pairs, no need to add to file_2
+            file_coords_map
+                .entry(file_1_name.to_string())
+                .or_default()
+                .insert(file_2_name.to_string(), common_indexes);
         }
     }
 
     // Save the file_coords_map to a file
     save_to_json(file_coords_map);
+    save_serializable_to_json(&hash_gpx_files(), &get_output_file_path(FILE_HASHES_FILE));
 
     println!("Done, look for the 'final.json' file in the 'output' folder :)");
 }
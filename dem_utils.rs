@@ -0,0 +1,200 @@
+///
+/// # dem_utils.rs
+/// This file contains the functions used to read elevations from a local DEM
+/// made of SRTM `.hgt` tiles (1 or 3 arc-second, named like `N42E000.hgt`).
+///
+/// ## Author
+/// * Tom Planche - <github.com/tomPlanche>
+
+// IMPORTS ===================================================================================================  IMPORTS
+use std::collections::{BTreeSet, HashMap};
+use std::fs::read;
+use std::path::PathBuf;
+
+use crate::gpx_utils::Coord;
+// END IMPORTS ==========================================================================================   END IMPORTS
+
+// VARIABLES ================================================================================================ VARIABLE
+// Value of the SRTM samples without data.
+const HGT_VOID: i16 = -32768;
+
+#[derive(Debug, Clone)]
+pub struct HgtTile {
+    // Latitude of the south edge and longitude of the west edge
+    pub lat: i32,
+    pub lon: i32,
+    // Number of samples per row and per column
+    pub size: usize,
+    // Rows from north to south, big-endian in the file
+    pub samples: Vec<i16>,
+}
+
+#[derive(Debug)]
+pub struct DemSampler {
+    pub folder: PathBuf,
+    // None when the tile file is missing or invalid
+    tiles: HashMap<String, Option<HgtTile>>,
+    pub missing_tiles: BTreeSet<String>,
+}
+// END VARIABLES =======================================================================================  END VARIABLES
+
+// FUNCTIONS ================================================================================================ FUNCTIONS
+///
+/// # hgt_tile_name
+/// Get the name of the SRTM tile containing a coordinate.
+///
+/// ## Arguments
+/// * `coord` - The coordinate.
+///
+/// ## Returns
+/// * `String` - The tile file name, like `N42E000.hgt`.
+#[allow(dead_code)]
+pub fn hgt_tile_name(coord: Coord) -> String {
+    let lat = coord.lat.floor() as i32;
+    let lon = coord.lon.floor() as i32;
+
+    format!(
+        "{}{:02}{}{:03}.hgt",
+        if lat >= 0 { 'N' } else { 'S' },
+        lat.abs(),
+        if lon >= 0 { 'E' } else { 'W' },
+        lon.abs()
+    )
+}
+
+impl HgtTile {
+    ///
+    /// # from_bytes
+    /// Read a tile from the content of a `.hgt` file.
+    ///
+    /// ## Arguments
+    /// * `bytes` - The content of the file.
+    /// * `lat` - The latitude of the south edge of the tile.
+    /// * `lon` - The longitude of the west edge of the tile.
+    ///
+    /// ## Returns
+    /// * `Option<HgtTile>` - The tile, None if the file is not a square of samples.
+    #[allow(dead_code)]
+    pub fn from_bytes(bytes: &[u8], lat: i32, lon: i32) -> Option<HgtTile> {
+        let size = ((bytes.len() / 2) as f64).sqrt() as usize;
+        if size < 2 || size * size * 2 != bytes.len() {
+            return None;
+        }
+
+        let samples: Vec<i16> = bytes
+            .chunks_exact(2)
+            .map(|pair| i16::from_be_bytes([pair[0], pair[1]]))
+            .collect();
+
+        Some(HgtTile { lat, lon, size, samples })
+    }
+
+    ///
+    /// # sample
+    /// Get the sample of a row and column, None for voids.
+    fn sample(&self, row: usize, column: usize) -> Option<f64> {
+        match self.samples[row * self.size + column] {
+            HGT_VOID => None,
+            value => Some(value as f64),
+        }
+    }
+
+    ///
+    /// # elevation
+    /// Get the elevation of a coordinate inside the tile with a bilinear interpolation.
+    ///
+    /// ## Arguments
+    /// * `coord` - The coordinate.
+    ///
+    /// ## Returns
+    /// * `Option<f64>` - The elevation (in meters), None if the coordinate is outside the tile or near a void.
+    #[allow(dead_code)]
+    pub fn elevation(&self, coord: Coord) -> Option<f64> {
+        let last = (self.size - 1) as f64;
+
+        // Position in samples, from the north-west corner
+        let x = (coord.lon - self.lon as f64) * last;
+        let y = (self.lat as f64 + 1.0 - coord.lat) * last;
+
+        if !(0.0..=last).contains(&x) || !(0.0..=last).contains(&y) {
+            return None;
+        }
+
+        let column = (x.floor() as usize).min(self.size - 2);
+        let row = (y.floor() as usize).min(self.size - 2);
+        let (dx, dy) = (x - column as f64, y - row as f64);
+
+        let top = self.sample(row, column)? * (1.0 - dx) + self.sample(row, column + 1)? * dx;
+        let bottom = self.sample(row + 1, column)? * (1.0 - dx) + self.sample(row + 1, column + 1)? * dx;
+
+        Some(top * (1.0 - dy) + bottom * dy)
+    }
+}
+
+impl DemSampler {
+    ///
+    /// # new
+    /// Create a sampler reading the tiles of a folder when they are first needed.
+    #[allow(dead_code)]
+    pub fn new(folder: PathBuf) -> DemSampler {
+        DemSampler {
+            folder,
+            tiles: HashMap::new(),
+            missing_tiles: BTreeSet::new(),
+        }
+    }
+
+    ///
+    /// # elevation
+    /// Get the elevation of a coordinate.
+    /// Missing tiles are recorded in `missing_tiles` instead of failing.
+    ///
+    /// ## Arguments
+    /// * `coord` - The coordinate.
+    ///
+    /// ## Returns
+    /// * `Option<f64>` - The elevation (in meters), None if the tile is missing or has a void there.
+    #[allow(dead_code)]
+    pub fn elevation(&mut self, coord: Coord) -> Option<f64> {
+        let name = hgt_tile_name(coord);
+
+        if !self.tiles.contains_key(&name) {
+            let tile = read(self.folder.join(&name))
+                .ok()
+                .and_then(|bytes| HgtTile::from_bytes(&bytes, coord.lat.floor() as i32, coord.lon.floor() as i32));
+
+            if tile.is_none() {
+                self.missing_tiles.insert(name.clone());
+            }
+            self.tiles.insert(name.clone(), tile);
+        }
+
+        self.tiles[&name].as_ref()?.elevation(coord)
+    }
+}
+
+#[test]
+fn test_hgt_tile_name() {
+    assert_eq!(hgt_tile_name(Coord { lat: 42.68, lon: 0.2 }), "N42E000.hgt");
+    assert_eq!(hgt_tile_name(Coord { lat: -0.5, lon: -77.03 }), "S01W078.hgt");
+}
+
+#[test]
+fn test_hgt_tile_elevation() {
+    // 3x3 samples: 1000 on the north row, 1100 in the middle, 1200 on the south row, one void
+    let values: [i16; 9] = [1000, 1000, 1000, 1100, 1100, 1100, 1200, 1200, HGT_VOID];
+    let bytes: Vec<u8> = values.iter().flat_map(|value| value.to_be_bytes()).collect();
+
+    let tile = HgtTile::from_bytes(&bytes, 42, 0).unwrap();
+
+    assert_eq!(tile.elevation(Coord { lat: 43.0, lon: 0.0 }), Some(1000.0));
+    assert_eq!(tile.elevation(Coord { lat: 42.75, lon: 0.25 }), Some(1050.0));
+    assert_eq!(tile.elevation(Coord { lat: 42.25, lon: 0.75 }), None);
+    assert_eq!(tile.elevation(Coord { lat: 44.0, lon: 0.0 }), None);
+}
+
+// END FUNCTIONS =======================================================================================  END FUNCTIONS
+
+//
+// * End of file dem_utils.rs
+//
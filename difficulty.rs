@@ -0,0 +1,141 @@
+/// # difficulty.rs
+/// This binary rates the difficulty of each GPX file in the 'assets' folder
+/// and saves the ranking, hardest first, in the 'difficulty.json' file of the 'output' folder.
+///
+/// Arguments:
+/// * preset (optional) - `effort` (default), `sac`, or the path of a json config
+///   with a name, weights, levels and optionally the length of the steepest section like the presets of `difficulty_utils`.
+///
+/// The final json will look like this:
+/// ```json
+/// {
+///   "name": "effort",
+///   "weights": { "per_km": 1.0, ... },
+///   "levels": [{ "label": "easy", "min_score": 0.0 }, ...],
+///   "steepest_section_length": 500.0,
+///   "ranking": [
+///     {
+///       "file": "file_1",
+///       "score": 44.0,
+///       "label": "very_hard",
+///       "inputs": { "length": 18517.8, "ascent": 1886.6, "steepest_section_grade": 49.4, "max_altitude": 2858.1, "has_elevation": true }
+///     },
+///     ...
+///   ]
+/// }
+/// ```
+///
+/// ## Author
+/// * Tom Planche - <github.com/tomPlanche>
+///
+/// ## Example
+/// >> cargo run --bin difficulty sac
+/// 1. EspadaIbonetCircuito.gpx     T5         (48.3)
+/// ...
+
+// IMPORTS ===================================================================================================  IMPORTS
+#[path = "gpx_utils.rs"]
+mod gpx_utils;
+
+#[path = "file_utils.rs"]
+mod file_utils;
+
+#[path = "utils.rs"]
+mod utils;
+
+#[path = "elevation_utils.rs"]
+mod elevation_utils;
+
+#[path = "analysis_utils.rs"]
+mod analysis_utils;
+
+#[path = "grade_utils.rs"]
+mod grade_utils;
+
+#[path = "difficulty_utils.rs"]
+mod difficulty_utils;
+
+use std::path::PathBuf;
+
+use ansi_term::Colour::Yellow;
+use serde::Serialize;
+
+use crate::difficulty_utils::{calc_difficulty_inputs, rank_difficulties, DifficultyConfig, DifficultyInputs, DifficultyRating};
+use crate::file_utils::{
+    get_output_file_path,
+    load_serializable_from_json,
+    look_4_files,
+    read_file_name,
+    read_gpx_points,
+    save_serializable_to_json,
+};
+use crate::gpx_utils::Point;
+// END IMPORTS ==========================================================================================   END IMPORTS
+
+// VARIABLES ================================================================================================ VARIABLE
+#[derive(Serialize)]
+struct DifficultyRanking {
+    #[serde(flatten)]
+    config: DifficultyConfig,
+    ranking: Vec<DifficultyRating>,
+}
+// END VARIABLES =======================================================================================  END VARIABLES
+
+// CODE ========================================================================================================= CODE
+fn main() {
+    let args: Vec<String> = std::env::args().collect();
+
+    if args.len() > 2 {
+        panic!("This binary takes at most one argument, the preset or the path of a json config");
+    }
+
+    let config: DifficultyConfig = match args.get(1).map(String::as_str) {
+        None | Some("effort") => DifficultyConfig::effort(),
+        Some("sac") => DifficultyConfig::sac(),
+        Some(path) => {
+            let mut config: DifficultyConfig = load_serializable_from_json(&PathBuf::from(path));
+            config.sort_levels();
+
+            config
+        }
+    };
+
+    let mut tracks: Vec<(String, DifficultyInputs)> = Vec::new();
+
+    let gpx_files: Vec<PathBuf> = look_4_files();
+    for file in &gpx_files {
+        let file_name: String = match read_file_name(file) {
+            Some(file_name) => file_name,
+            None => panic!("Could not read the file name of {:?}", file),
+        };
+
+        let points: Vec<Point> = match read_gpx_points(file) {
+            Some(points) => points,
+            None => panic!("Could not read the file {:?}", file),
+        };
+
+        tracks.push((file_name, calc_difficulty_inputs(&points, config.steepest_section_length)));
+    }
+
+    let ranking: Vec<DifficultyRating> = rank_difficulties(tracks, &config);
+
+    for (rank, rating) in ranking.iter().enumerate() {
+        let line = format!("{}. {:<28} {:<10} ({:.1})", rank + 1, rating.file, rating.label, rating.score);
+
+        if rating.inputs.has_elevation {
+            println!("{}", line);
+        } else {
+            println!("{} {}", line, Yellow.paint("no elevation"));
+        }
+    }
+
+    save_serializable_to_json(
+        &DifficultyRanking { config, ranking },
+        &get_output_file_path("difficulty.json"),
+    );
+}
+// END CODE =======================================================================================  END COMPONENT
+
+//
+// * End of file /difficulty.rs
+//
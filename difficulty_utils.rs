@@ -0,0 +1,322 @@
+///
+/// # difficulty_utils.rs
+/// This file contains the difficulty score of a track, combining its length, ascent,
+/// steepest grade and altitude with configurable weights, and the presets of the app.
+///
+/// ## Author
+/// * Tom Planche - <github.com/tomPlanche>
+
+// IMPORTS ===================================================================================================  IMPORTS
+use serde::{Deserialize, Serialize};
+
+use crate::analysis_utils::calc_track_length;
+use crate::elevation_utils::{calc_ascent_descent, DEFAULT_ELEVATION_FILTER};
+use crate::gpx_utils::{Coord, Point};
+use crate::grade_utils::{calc_grades, find_steepest_section};
+// END IMPORTS ==========================================================================================   END IMPORTS
+
+// VARIABLES ================================================================================================ VARIABLE
+// Default length (in meters) of the section used for the steepest grade. The score rates the sustained
+// steepness of a trail: over a few hundred meters the grade depends on the terrain, while over a single
+// segment it depends mostly on the elevation noise and on where the points were recorded.
+#[allow(dead_code)]
+pub const STEEPEST_SECTION_LENGTH: f64 = 500.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct DifficultyInputs {
+    // In meters
+    pub length: f64,
+    pub ascent: f64,
+    // Absolute average grade of the steepest section of the config length (in %)
+    pub steepest_section_grade: f64,
+    pub max_altitude: f64,
+    // Without elevation, the ascent, grade and altitude are 0
+    pub has_elevation: bool,
+}
+
+// Points given per unit of each input
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct DifficultyWeights {
+    pub per_km: f64,
+    pub per_100m_ascent: f64,
+    pub per_grade_percent: f64,
+    // Per 100m above `altitude_base`
+    pub per_100m_altitude: f64,
+    pub altitude_base: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DifficultyLevel {
+    pub label: String,
+    pub min_score: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DifficultyConfig {
+    pub name: String,
+    pub weights: DifficultyWeights,
+    // Sorted by increasing `min_score`
+    pub levels: Vec<DifficultyLevel>,
+    // Length (in meters) of the section of `steepest_section_grade`
+    #[serde(default = "default_steepest_section_length")]
+    pub steepest_section_length: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DifficultyRating {
+    pub file: String,
+    pub score: f64,
+    pub label: String,
+    pub inputs: DifficultyInputs,
+}
+// END VARIABLES =======================================================================================  END VARIABLES
+
+// FUNCTIONS ================================================================================================ FUNCTIONS
+///
+/// # default_steepest_section_length
+/// The length of the steepest section of the configs without one.
+fn default_steepest_section_length() -> f64 {
+    STEEPEST_SECTION_LENGTH
+}
+
+///
+/// # levels
+/// Build the levels of a preset from their labels and minimum scores.
+fn levels(levels: &[(&str, f64)]) -> Vec<DifficultyLevel> {
+    levels
+        .iter()
+        .map(|(label, min_score)| DifficultyLevel {
+            label: label.to_string(),
+            min_score: *min_score,
+        })
+        .collect()
+}
+
+impl DifficultyConfig {
+    ///
+    /// # sac
+    /// Preset labelled like the SAC hiking scale (T1 to T6).
+    /// The SAC scale rates the terrain, so the grade and the altitude weigh the most here.
+    #[allow(dead_code)]
+    pub fn sac() -> DifficultyConfig {
+        DifficultyConfig {
+            name: "sac".to_string(),
+            weights: DifficultyWeights {
+                per_km: 0.3,
+                per_100m_ascent: 0.5,
+                per_grade_percent: 0.4,
+                per_100m_altitude: 1.0,
+                altitude_base: 1500.0,
+            },
+            levels: levels(&[("T1", 0.0), ("T2", 10.0), ("T3", 20.0), ("T4", 30.0), ("T5", 40.0), ("T6", 50.0)]),
+            steepest_section_length: STEEPEST_SECTION_LENGTH,
+        }
+    }
+
+    ///
+    /// # effort
+    /// Preset based on the length and the ascent, for the badge of the app.
+    #[allow(dead_code)]
+    pub fn effort() -> DifficultyConfig {
+        DifficultyConfig {
+            name: "effort".to_string(),
+            weights: DifficultyWeights {
+                per_km: 1.0,
+                per_100m_ascent: 1.0,
+                per_grade_percent: 0.1,
+                per_100m_altitude: 0.2,
+                altitude_base: 2000.0,
+            },
+            levels: levels(&[("easy", 0.0), ("moderate", 10.0), ("hard", 20.0), ("very_hard", 35.0)]),
+            steepest_section_length: STEEPEST_SECTION_LENGTH,
+        }
+    }
+
+    ///
+    /// # score
+    /// Calculate the difficulty score of a track.
+    ///
+    /// ## Arguments
+    /// * `inputs` - The inputs of the track.
+    ///
+    /// ## Returns
+    /// * `f64` - The score, higher is harder.
+    #[allow(dead_code)]
+    pub fn score(&self, inputs: &DifficultyInputs) -> f64 {
+        let weights = &self.weights;
+
+        inputs.length / 1000.0 * weights.per_km
+            + inputs.ascent / 100.0 * weights.per_100m_ascent
+            + inputs.steepest_section_grade * weights.per_grade_percent
+            + (inputs.max_altitude - weights.altitude_base).max(0.0) / 100.0 * weights.per_100m_altitude
+    }
+
+    ///
+    /// # sort_levels
+    /// Sort the levels by increasing minimum score, as `label` expects them,
+    /// for the configs loaded from a json file.
+    #[allow(dead_code)]
+    pub fn sort_levels(&mut self) {
+        self.levels.sort_by(|a, b| a.min_score.total_cmp(&b.min_score));
+    }
+
+    ///
+    /// # label
+    /// Get the label of a score: the last level whose minimum score is reached.
+    ///
+    /// ## Arguments
+    /// * `score` - The score.
+    ///
+    /// ## Returns
+    /// * `String` - The label, empty without levels.
+    #[allow(dead_code)]
+    pub fn label(&self, score: f64) -> String {
+        self.levels
+            .iter()
+            .take_while(|level| score >= level.min_score)
+            .last()
+            .or(self.levels.first())
+            .map_or(String::new(), |level| level.label.clone())
+    }
+}
+
+///
+/// # calc_difficulty_inputs
+/// Calculate the inputs of the difficulty score of a track.
+///
+/// ## Arguments
+/// * `points` - The points of the track.
+/// * `steepest_section_length` - The length of the steepest section (in meters).
+///
+/// ## Returns
+/// * `DifficultyInputs` - The inputs.
+#[allow(dead_code)]
+pub fn calc_difficulty_inputs(points: &[Point], steepest_section_length: f64) -> DifficultyInputs {
+    let coords: Vec<Coord> = points.iter().map(|point| point.coords).collect();
+    let (ascent, _) = calc_ascent_descent(points, DEFAULT_ELEVATION_FILTER);
+
+    DifficultyInputs {
+        length: calc_track_length(&coords),
+        ascent,
+        steepest_section_grade: find_steepest_section(&calc_grades(points), steepest_section_length)
+            .map_or(0.0, |section| section.grade.abs()),
+        max_altitude: points.iter().filter_map(|point| point.elevation).fold(0.0, f64::max),
+        has_elevation: points.iter().any(|point| point.elevation.is_some()),
+    }
+}
+
+///
+/// # rank_difficulties
+/// Rate tracks with a config and sort them from the hardest to the easiest.
+///
+/// ## Arguments
+/// * `tracks` - The file names and inputs of the tracks.
+/// * `config` - The config used to score them.
+///
+/// ## Returns
+/// * `Vec<DifficultyRating>` - The ratings, hardest first, by file name on a tie.
+#[allow(dead_code)]
+pub fn rank_difficulties(tracks: Vec<(String, DifficultyInputs)>, config: &DifficultyConfig) -> Vec<DifficultyRating> {
+    let mut ratings: Vec<DifficultyRating> = tracks
+        .into_iter()
+        .map(|(file, inputs)| {
+            let score = config.score(&inputs);
+
+            DifficultyRating {
+                file,
+                score,
+                label: config.label(score),
+                inputs,
+            }
+        })
+        .collect();
+
+    ratings.sort_by(|a, b| b.score.total_cmp(&a.score).then_with(|| a.file.cmp(&b.file)));
+
+    ratings
+}
+
+#[test]
+fn test_difficulty_label() {
+    let config = DifficultyConfig::effort();
+
+    assert_eq!(config.label(0.0), "easy");
+    assert_eq!(config.label(12.5), "moderate");
+    assert_eq!(config.label(100.0), "very_hard");
+    assert_eq!(config.label(-1.0), "easy");
+}
+
+#[test]
+fn test_rank_difficulties() {
+    let inputs = |length: f64, ascent: f64| DifficultyInputs {
+        length,
+        ascent,
+        steepest_section_grade: 0.0,
+        max_altitude: 0.0,
+        has_elevation: true,
+    };
+
+    let ratings = rank_difficulties(
+        vec![
+            ("short.gpx".to_string(), inputs(5000.0, 200.0)),
+            ("long.gpx".to_string(), inputs(20000.0, 1200.0)),
+        ],
+        &DifficultyConfig::effort(),
+    );
+
+    assert_eq!(ratings[0].file, "long.gpx");
+    assert_eq!((ratings[0].score, ratings[0].label.as_str()), (32.0, "hard"));
+    assert_eq!((ratings[1].score, ratings[1].label.as_str()), (7.0, "easy"));
+}
+
+#[test]
+fn test_calc_difficulty_inputs() {
+    // 2km north, points every ~100m, climbing 30m per 100m between the 5th and the 11th point
+    let points: Vec<Point> = (0..21)
+        .map(|i| Point {
+            coords: Coord { lat: 42.0 + i as f64 * 0.0009, lon: 0.0 },
+            name: None,
+            description: None,
+            elevation: Some(1000.0 + 30.0 * (i.clamp(5, 11) - 5) as f64),
+        })
+        .collect();
+
+    let inputs = calc_difficulty_inputs(&points, STEEPEST_SECTION_LENGTH);
+    assert!((inputs.length - 2001.5).abs() < 1.0);
+    assert!((inputs.ascent - 180.0).abs() < 1e-9);
+    assert!((inputs.steepest_section_grade - 30.0).abs() < 0.1);
+    assert_eq!(inputs.max_altitude, 1180.0);
+
+    // A section longer than the climb averages it with the flat
+    assert!((calc_difficulty_inputs(&points, 900.0).steepest_section_grade - 20.0).abs() < 0.1);
+
+    // 2 + 1.8 + 30 * 0.1 = 6.8 and 0.6 + 0.9 + 30 * 0.4 = 13.5
+    let effort = DifficultyConfig::effort();
+    let sac = DifficultyConfig::sac();
+    assert!((effort.score(&inputs) - 6.8).abs() < 0.05);
+    assert_eq!(effort.label(effort.score(&inputs)), "easy");
+    assert!((sac.score(&inputs) - 13.5).abs() < 0.05);
+    assert_eq!(sac.label(sac.score(&inputs)), "T2");
+}
+
+#[test]
+fn test_sort_levels() {
+    let mut config: DifficultyConfig = serde_json::from_str(
+        r#"{
+            "name": "custom",
+            "weights": { "per_km": 1.0, "per_100m_ascent": 1.0, "per_grade_percent": 0.0, "per_100m_altitude": 0.0, "altitude_base": 0.0 },
+            "levels": [{ "label": "hard", "min_score": 20.0 }, { "label": "easy", "min_score": 0.0 }]
+        }"#,
+    )
+    .unwrap();
+    config.sort_levels();
+
+    assert_eq!(config.steepest_section_length, STEEPEST_SECTION_LENGTH);
+    assert_eq!((config.label(5.0), config.label(25.0)), ("easy".to_string(), "hard".to_string()));
+}
+
+// END FUNCTIONS =======================================================================================  END FUNCTIONS
+
+//
+// * End of file difficulty_utils.rs
+//
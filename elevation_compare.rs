@@ -0,0 +1,98 @@
+/// # elevation_compare.rs
+/// This binary compares the ascent and descent of the GPX files with the raw elevation
+/// and with each smoothing filter, to choose the default filter of the app.
+///
+/// Arguments:
+/// * files (optional) - The names of GPX files in the 'assets' folder, all of them by default.
+///
+/// ## Author
+/// * Tom Planche - <github.com/tomPlanche>
+///
+/// ## Example
+/// >> cargo run --bin elevation_compare EspadaIbonetCircuito.gpx
+/// EspadaIbonetCircuito.gpx
+///   raw                   ascent:  2073m  descent:  2074m
+///   median(5)             ascent:  1910m  descent:  1912m
+///   ...
+
+// IMPORTS ===================================================================================================  IMPORTS
+#[path = "gpx_utils.rs"]
+mod gpx_utils;
+
+#[path = "file_utils.rs"]
+mod file_utils;
+
+#[path = "utils.rs"]
+mod utils;
+
+#[path = "elevation_utils.rs"]
+mod elevation_utils;
+
+use std::path::PathBuf;
+
+use ansi_term::Colour::{Green, Yellow};
+
+use crate::elevation_utils::{calc_ascent_descent, ElevationFilter, DEFAULT_ELEVATION_FILTER};
+use crate::file_utils::{file_name_to_path_buf, look_4_files, read_file_name, read_gpx_points};
+use crate::gpx_utils::Point;
+// END IMPORTS ==========================================================================================   END IMPORTS
+
+// VARIABLES ================================================================================================ VARIABLE
+const FILTERS: [ElevationFilter; 8] = [
+    ElevationFilter::Raw,
+    ElevationFilter::Median(5),
+    ElevationFilter::Median(9),
+    ElevationFilter::SavitzkyGolay(7),
+    ElevationFilter::SavitzkyGolay(15),
+    ElevationFilter::Hysteresis(2.0),
+    ElevationFilter::Hysteresis(5.0),
+    ElevationFilter::Hysteresis(10.0),
+];
+// END VARIABLES ======================================================================================= END VARIABLES
+
+// CODE ========================================================================================================= CODE
+fn main() {
+    let mut gpx_files: Vec<PathBuf> = std::env::args()
+        .skip(1)
+        .map(|file_name| file_name_to_path_buf(&file_name))
+        .collect();
+
+    if gpx_files.is_empty() {
+        gpx_files = look_4_files();
+    }
+
+    for file in &gpx_files {
+        let file_name: String = match read_file_name(file) {
+            Some(file_name) => file_name,
+            None => panic!("Could not read the file name of {:?}", file),
+        };
+
+        let points: Vec<Point> = match read_gpx_points(file) {
+            Some(points) => points,
+            None => panic!("Could not read the file {:?}", file),
+        };
+
+        println!("{}", file_name);
+
+        if points.iter().all(|point| point.elevation.is_none()) {
+            println!("  {}", Yellow.paint("no elevation"));
+            continue;
+        }
+
+        for filter in FILTERS {
+            let (ascent, descent) = calc_ascent_descent(&points, filter);
+            let line = format!("  {:<20}  ascent: {:>5.0}m  descent: {:>5.0}m", filter.to_string(), ascent, descent);
+
+            if filter == DEFAULT_ELEVATION_FILTER {
+                println!("{} {}", line, Green.paint("(default)"));
+            } else {
+                println!("{}", line);
+            }
+        }
+    }
+}
+// END CODE =======================================================================================  END COMPONENT
+
+//
+// * End of file /elevation_compare.rs
+//
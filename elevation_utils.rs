@@ -0,0 +1,221 @@
+///
+/// # elevation_utils.rs
+/// This file contains the elevation smoothing filters
+/// and the ascent/descent calculations using them.
+///
+/// ## Author
+/// * Tom Planche - <github.com/tomPlanche>
+
+// IMPORTS ===================================================================================================  IMPORTS
+use std::fmt::{Display, Formatter};
+
+use serde::{Deserialize, Serialize};
+
+use crate::gpx_utils::Point;
+// END IMPORTS ==========================================================================================   END IMPORTS
+
+// VARIABLES ================================================================================================ VARIABLE
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ElevationFilter {
+    // The recorded values
+    Raw,
+    // Median of a window of points (odd size)
+    Median(usize),
+    // Savitzky–Golay quadratic fit on a window of points (odd size)
+    SavitzkyGolay(usize),
+    // Changes smaller than a threshold (in meters) are ignored
+    Hysteresis(f64),
+}
+
+// The filter used by the ascent/descent calculations of the other tools.
+#[allow(dead_code)]
+pub const DEFAULT_ELEVATION_FILTER: ElevationFilter = ElevationFilter::Hysteresis(5.0);
+
+impl Display for ElevationFilter {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ElevationFilter::Raw => write!(f, "raw"),
+            ElevationFilter::Median(window) => write!(f, "median({})", window),
+            ElevationFilter::SavitzkyGolay(window) => write!(f, "savitzky_golay({})", window),
+            ElevationFilter::Hysteresis(threshold) => write!(f, "hysteresis({}m)", threshold),
+        }
+    }
+}
+// END VARIABLES =======================================================================================  END VARIABLES
+
+// FUNCTIONS ================================================================================================ FUNCTIONS
+///
+/// # median_filter
+/// Replace each value by the median of the `window` values around it.
+/// The window is shortened at both ends.
+fn median_filter(elevations: &[f64], window: usize) -> Vec<f64> {
+    let half = window / 2;
+
+    (0..elevations.len())
+        .map(|index| {
+            let mut neighbours: Vec<f64> =
+                elevations[index.saturating_sub(half)..(index + half + 1).min(elevations.len())].to_vec();
+            neighbours.sort_by(|a, b| a.partial_cmp(b).unwrap());
+
+            neighbours[neighbours.len() / 2]
+        })
+        .collect()
+}
+
+///
+/// # savitzky_golay_filter
+/// Replace each value by a quadratic least-squares fit of the `window` values around it.
+/// The `window / 2` values at both ends are kept as they are.
+fn savitzky_golay_filter(elevations: &[f64], window: usize) -> Vec<f64> {
+    let half = (window / 2) as i64;
+    if half == 0 || elevations.len() < window {
+        return elevations.to_vec();
+    }
+
+    // Quadratic convolution coefficients for a window of 2 * half + 1 points
+    let norm = ((2 * half + 1) * (4 * half * half + 4 * half - 3)) as f64;
+    let coefficients: Vec<f64> = (-half..=half)
+        .map(|i| (3 * (3 * half * half + 3 * half - 1 - 5 * i * i)) as f64 / norm)
+        .collect();
+
+    let half = half as usize;
+
+    (0..elevations.len())
+        .map(|index| {
+            if index < half || index + half >= elevations.len() {
+                return elevations[index];
+            }
+
+            elevations[index - half..=index + half]
+                .iter()
+                .zip(&coefficients)
+                .map(|(elevation, coefficient)| elevation * coefficient)
+                .sum()
+        })
+        .collect()
+}
+
+///
+/// # hysteresis_filter
+/// Only follow the elevation once it moved by more than `threshold` from the last kept value.
+fn hysteresis_filter(elevations: &[f64], threshold: f64) -> Vec<f64> {
+    let mut kept: Option<f64> = None;
+
+    elevations
+        .iter()
+        .map(|elevation| match kept {
+            Some(value) if (elevation - value).abs() <= threshold => value,
+            _ => {
+                kept = Some(*elevation);
+                *elevation
+            }
+        })
+        .collect()
+}
+
+///
+/// # smooth_elevations
+/// Apply an elevation filter.
+///
+/// ## Arguments
+/// * `elevations` - The elevations of the track.
+/// * `filter` - The filter to apply.
+///
+/// ## Returns
+/// * `Vec<f64>` - The filtered elevations.
+#[allow(dead_code)]
+pub fn smooth_elevations(elevations: &[f64], filter: ElevationFilter) -> Vec<f64> {
+    match filter {
+        ElevationFilter::Raw => elevations.to_vec(),
+        ElevationFilter::Median(window) => median_filter(elevations, window),
+        ElevationFilter::SavitzkyGolay(window) => savitzky_golay_filter(elevations, window),
+        ElevationFilter::Hysteresis(threshold) => hysteresis_filter(elevations, threshold),
+    }
+}
+
+///
+/// # calc_ascent_descent
+/// Calculate the total ascent and descent of a list of points.
+/// Points without elevation are skipped.
+///
+/// ## Arguments
+/// * `points` - The points to go through.
+/// * `filter` - The filter applied to the elevations first.
+///
+/// ## Returns
+/// * `(f64, f64)` - The ascent and the descent (in meters).
+#[allow(dead_code)]
+pub fn calc_ascent_descent(points: &[Point], filter: ElevationFilter) -> (f64, f64) {
+    let elevations: Vec<f64> = points.iter().filter_map(|point| point.elevation).collect();
+
+    smooth_elevations(&elevations, filter)
+        .windows(2)
+        .fold((0.0, 0.0), |(ascent, descent), pair| {
+            let delta = pair[1] - pair[0];
+
+            if delta > 0.0 {
+                (ascent + delta, descent)
+            } else {
+                (ascent, descent - delta)
+            }
+        })
+}
+
+#[cfg(test)]
+fn test_points(elevations: &[Option<f64>]) -> Vec<Point> {
+    use crate::gpx_utils::Coord;
+
+    elevations
+        .iter()
+        .map(|elevation| Point {
+            coords: Coord { lat: 42.0, lon: 0.0 },
+            name: None,
+            description: None,
+            elevation: *elevation,
+        })
+        .collect()
+}
+
+#[test]
+fn test_calc_ascent_descent() {
+    let points = test_points(&[Some(1000.0), Some(1010.0), None, Some(1005.0)]);
+
+    assert_eq!(calc_ascent_descent(&points, ElevationFilter::Raw), (10.0, 5.0));
+}
+
+#[test]
+fn test_filters_remove_noise() {
+    // A steady climb of 1m per point with +/- 2m of noise
+    let elevations: Vec<Option<f64>> = (0..100)
+        .map(|i| Some(1000.0 + i as f64 + if i % 2 == 0 { 2.0 } else { -2.0 }))
+        .collect();
+    let points = test_points(&elevations);
+
+    let (raw_ascent, _) = calc_ascent_descent(&points, ElevationFilter::Raw);
+    assert!(raw_ascent > 200.0);
+
+    for filter in [
+        ElevationFilter::Median(5),
+        ElevationFilter::SavitzkyGolay(7),
+        ElevationFilter::Hysteresis(5.0),
+    ] {
+        let (ascent, _) = calc_ascent_descent(&points, filter);
+        assert!((90.0..=110.0).contains(&ascent), "{} gave {}", filter, ascent);
+    }
+}
+
+#[test]
+fn test_savitzky_golay_keeps_parabolas() {
+    let elevations: Vec<f64> = (0..20).map(|i| (i * i) as f64).collect();
+
+    for (smoothed, elevation) in smooth_elevations(&elevations, ElevationFilter::SavitzkyGolay(7)).iter().zip(&elevations) {
+        assert!((smoothed - elevation).abs() < 1e-9);
+    }
+}
+
+// END FUNCTIONS =======================================================================================  END FUNCTIONS
+
+//
+// * End of file elevation_utils.rs
+//
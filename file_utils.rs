@@ -5,16 +5,10 @@
 /// * Tom Planche - <github.com/tomPlanche>
 
 // IMPORTS ===================================================================================================  IMPORTS
-mod utils;
-
-#[path = "gpx_utils.rs"]
-mod gpx_utils;
-
-
-use gpx::{read};
+use gpx::{read, write, Gpx, GpxVersion, Track, TrackSegment, Waypoint};
 
 use std::{
-    collections::HashMap,
+    collections::{BTreeMap, HashMap},
     fs::{
         File,
         read_dir
@@ -29,16 +23,19 @@ use std::{
         PathBuf
     },
 };
-use std::fs::OpenOptions;
 
-use json::JsonValue;
+use rayon::prelude::*;
+use serde::{de::DeserializeOwned, Serialize};
+use sha2::{Digest, Sha256};
 
-use utils::{FileCoordsHM};
-use gpx_utils::Coord;
+use crate::utils::{FileCoordsHM};
+use crate::gpx_utils::{Coord, Point};
 // END IMPORTS ==========================================================================================   END IMPORTS
 
 // VARIABLES ================================================================================================ VARIABLE
-const PATH_PREFIX: &str = "../../assets";
+// Name of the manifest of the hashes of the GPX files compared in 'final.json', in the 'output' folder.
+#[allow(dead_code)]
+pub const FILE_HASHES_FILE: &str = "final_manifest.json";
 // END VARIABLES ======================================================================================= END VARIABLES
 
 // FUNCTIONS ================================================================================================ FUNCTIONS
@@ -71,9 +68,27 @@ pub fn get_final_json_path() -> PathBuf {
     path
 }
 
+///
+/// # get_output_file_path
+/// Get the path to a file in the 'output' directory.
+///
+/// ## Arguments
+/// * `file_name(&str)` - The name of the file.
+///
+/// ## Returns
+/// * `PathBuf` - The path to the file.
+#[allow(dead_code)]
+pub fn get_output_file_path(file_name: &str) -> PathBuf {
+    let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR")); // Get the project's root directory
+    path.push("./output");
+    path.push(file_name);
+
+    path
+}
+
 ///
 /// # look_4_files
-/// Look for GPX files in the 'assets' directory and return a vector of PathBuf
+/// Look for GPX files in the 'assets' directory and return a vector of PathBuf, sorted by name
 ///
 /// ## Returns
 /// * `Vec<PathBuf>` - A vector of PathBuf.
@@ -81,7 +96,7 @@ pub fn get_final_json_path() -> PathBuf {
 pub fn look_4_files() -> Vec<PathBuf> {
     let paths = read_dir("./assets/").unwrap();
 
-    paths
+    let mut files: Vec<PathBuf> = paths
         .filter_map(|entry| {
             let entry = entry.unwrap();
             let path = entry.path();
@@ -92,7 +107,12 @@ pub fn look_4_files() -> Vec<PathBuf> {
 
             None
         })
-        .collect()
+        .collect();
+
+    // The order of the folder depends on the file system
+    files.sort();
+
+    files
 }
 
 ///
@@ -129,6 +149,89 @@ pub fn read_gpx_file(path: &PathBuf) -> Option<Vec<Coord>> {
     }
 }
 
+///
+/// # read_gpx_files
+/// Read GPX files in parallel, on every CPU core (or the threads of the global rayon pool).
+///
+/// ## Arguments
+/// * `paths(&[PathBuf])` - The paths to the GPX files
+///
+/// ## Returns
+/// * `BTreeMap<String, Vec<Coord>>` - The coordinates of each file, by file name
+#[allow(dead_code)]
+pub fn read_gpx_files(paths: &[PathBuf]) -> BTreeMap<String, Vec<Coord>> {
+    paths
+        .par_iter()
+        .map(|path| {
+            let file_name: String = match read_file_name(path) {
+                Some(file_name) => file_name,
+                None => panic!("Could not read the file name of {:?}", path),
+            };
+
+            match read_gpx_file(path) {
+                Some(coords) => (file_name, coords),
+                None => panic!("Could not read the file {:?}", path),
+            }
+        })
+        .collect()
+}
+
+///
+/// # read_gpx_document
+/// Read and parse a GPX file, keeping its tracks and segments as they are.
+///
+/// ## Arguments
+/// * `path(&Path)` - The path to the GPX file
+///
+/// ## Returns
+/// * `Result<Gpx, String>` - The parsed file, or why it could not be read.
+#[allow(dead_code)]
+pub fn read_gpx_document(path: &Path) -> Result<Gpx, String> {
+    let file = File::open(path).map_err(|error| error.to_string())?;
+
+    read(BufReader::new(file)).map_err(|error| error.to_string())
+}
+
+///
+/// # read_gpx_points
+/// Read a GPX file and return its track points, keeping their elevation,
+/// name and description.
+///
+/// ## Arguments
+/// * `path(&Path)` - The path to the GPX file
+///
+/// ## Returns
+/// * `Option<Vec<Point>>` - A vector of points, None if the file could not be parsed.
+#[allow(dead_code)]
+pub fn read_gpx_points(path: &Path) -> Option<Vec<Point>> {
+    match read(BufReader::new(File::open(path).unwrap())) {
+        Ok(gpx) => {
+            let mut points: Vec<Point> = Vec::new();
+            for track in gpx.tracks {
+                for segment in track.segments {
+                    for point in segment.points {
+                        points.push(Point {
+                            coords: Coord {
+                                lat: point.point().y(),
+                                lon: point.point().x(),
+                            },
+                            name: point.name.clone(),
+                            description: point.description.clone(),
+                            elevation: point.elevation,
+                        });
+                    }
+                }
+            }
+
+            Some(points)
+        }
+        Err(error) => {
+            println!("Error: : {}", error);
+            None
+        }
+    }
+}
+
 /// # read_file_name
 /// Read a file name from a PathBuf
 ///
@@ -149,6 +252,21 @@ pub fn read_file_name(path: &PathBuf) -> Option<String> {
     None
 }
 
+///
+/// # file_stem
+/// Remove the extension of a file name, the other dots of the name being kept,
+/// for the names of the files generated from it.
+///
+/// ## Arguments
+/// * `file_name(&str)` - The file name
+///
+/// ## Returns
+/// * `&str` - The file name without its extension
+#[allow(dead_code)]
+pub fn file_stem(file_name: &str) -> &str {
+    Path::new(file_name).file_stem().and_then(|stem| stem.to_str()).unwrap_or(file_name)
+}
+
 ///
 /// # save_to_json
 /// Save the HashMap<&str, HashMap<&str, Vec<(usize, usize)>>> to a JSON file.
@@ -166,8 +284,14 @@ pub fn save_to_json(
     // Create the file
     let mut file = File::create(get_final_json_path()).unwrap();
 
+    // Sorted by file names, so the same comparison always writes the same file
+    let sorted_map: BTreeMap<String, BTreeMap<String, Vec<(usize, usize)>>> = file_coords_map
+        .into_iter()
+        .map(|(file_name, other_files)| (file_name, other_files.into_iter().collect()))
+        .collect();
+
     // Write the HashMap to the file
-    match file.write_all(serde_json::to_string(&file_coords_map).unwrap().as_bytes()) {
+    match file.write_all(serde_json::to_string(&sorted_map).unwrap().as_bytes()) {
         Ok(_) => {
             println!("Successfully saved to: {}", get_final_json_path().display());
             true
@@ -179,18 +303,45 @@ pub fn save_to_json(
     }
 }
 
-/// # load_from_json
-/// Load a HashMap<&str, HashMap<&str, Vec<(usize, usize)>>> from a JSON file.
+///
+/// # save_serializable_to_json
+/// Save any serializable value to a JSON file.
 ///
 /// ## Arguments
-/// * `file_path(&PathBuf)` - The path to the JSON file
+/// * `value(&T)` - The value to save
+/// * `file_destination(&PathBuf)` - The path to the JSON file
 ///
 /// ## Returns
-/// * `Result<HashMap<&str, HashMap<&str, Vec<(usize, usize)>>>` - A Result containing the loaded HashMap or an error
+/// * `bool` - True if the value was saved, false otherwise
 #[allow(dead_code)]
-pub fn load_from_json(
-    file_path: &PathBuf,
-) -> FileCoordsHM {
+pub fn save_serializable_to_json<T: Serialize>(value: &T, file_destination: &Path) -> bool {
+    // Create the file
+    let mut file = File::create(file_destination).unwrap();
+
+    // Write the value to the file
+    match file.write_all(serde_json::to_string(value).unwrap().as_bytes()) {
+        Ok(_) => {
+            println!("Successfully saved to: {}", file_destination.display());
+            true
+        }
+        Err(error) => {
+            println!("Error: {}", error);
+            false
+        }
+    }
+}
+
+///
+/// # load_serializable_from_json
+/// Load any deserializable value from a JSON file.
+///
+/// ## Arguments
+/// * `file_path(&Path)` - The path to the JSON file
+///
+/// ## Returns
+/// * `T` - The loaded value
+#[allow(dead_code)]
+pub fn load_serializable_from_json<T: DeserializeOwned>(file_path: &Path) -> T {
     // Open the file
     let mut file = File::open(file_path).expect("The file could not be opened");
 
@@ -198,223 +349,134 @@ pub fn load_from_json(
     let mut json_string = String::new();
     file.read_to_string(&mut json_string).expect("The file could not be read");
 
-    // Parse the JSON string into a HashMap
-    let result: FileCoordsHM =
-        serde_json::from_str(&json_string).expect("The JSON string could not be parsed");
-
-
-    result
+    serde_json::from_str(&json_string).expect("The JSON string could not be parsed")
 }
 
 ///
-/// # folder_contains_folder
-/// Checks if a folder contains a folder.
+/// # is_file_up_to_date
+/// Check if a generated file has the content it would be generated with.
 ///
 /// ## Arguments
-/// * `folder_path` - The path of the folder to check
+/// * `file_path(&Path)` - The path to the generated file
+/// * `content(&str)` - The content it would be generated with
 ///
 /// ## Returns
-/// * `bool` - True if the folder contains a folder, false otherwise
+/// * `bool` - True if the file exists with this content, false otherwise
 #[allow(dead_code)]
-pub fn folder_contains_folder(folder_path: &Path) -> bool {
-    // Iterate over the folder.
-    // If the element is a file, add it to the final json.
-    // If the element is a folder, add it to the final json and iterate over it.
-    for element in read_dir(&folder_path).unwrap() {
-        if !element.is_ok() {
-            // If the element is not ok, skip it
-            continue;
-        }
+pub fn is_file_up_to_date(file_path: &Path, content: &str) -> bool {
+    let mut existing = String::new();
 
-        let element = element.unwrap(); // Get the element
-        let element_path = element.path(); // Get the path of the element
-
-        if element_path.is_dir() {
-            return true;
-        }
+    match File::open(file_path) {
+        Ok(mut file) => file.read_to_string(&mut existing).is_ok() && existing == content,
+        Err(_) => false,
     }
-
-    false
-}
-
-#[derive(Copy, Clone, PartialOrd, PartialEq)]
-#[allow(dead_code)]
-pub enum Mode {
-    Tiles,
-    Files
 }
 
 ///
-/// # iterate_over_folder
-/// Iterates over a folder and returns a json with the files and folders.
-/// If the element is a file, add it to the final json.
-/// If the element is a folder, add it to the final json and iterate over it.
+/// # hash_file
+/// Hash the content of a file, to detect its changes.
 ///
 /// ## Arguments
-/// * `final_json` - The json to add the files and folders to.
-/// * `folder_path` - The path of the folder to iterate over.
-/// * `mode` - The mode to use.
-/// * `Optional<&str>` - The part of the folder path to remove.
-/// * `Optional<&PathBuf>` - The file to write to.
+/// * `path(&Path)` - The path to the file
 ///
 /// ## Returns
-/// * `json::JsonValue` - The json with the files and folders.
+/// * `Option<String>` - The hexadecimal SHA-256 of the content, None if the file could not be read
 #[allow(dead_code)]
-pub fn iterate_over_folder(
-    mut final_json: JsonValue,
-    folder_path: &Path,
-    mode: Mode,
-    folder_part_to_remove: Option<String>,
-    file_to_write_to: Option<&PathBuf>,
-) -> JsonValue {
-    // Check if the folder exists
-    if !folder_path.exists() {
-        panic!("The folder {:?} does not exist - ALED", folder_path);
-    }
-
-    // Iterate over the folder.
-    // If the element is a file, add it to the final json.
-    // If the element is a folder, add it to the final json and iterate over it.
-    for element in read_dir(&folder_path).unwrap() {
-        if !element.is_ok() {
-            // If the element is not ok, skip it
-            continue;
-        }
-
-        let element = element.unwrap(); // Get the element
-        let element_path = element.path(); // Get the path of the element
-        let element_name = element_path.file_name().unwrap().to_str().unwrap(); // Get the name of the element
-
-        // since at the root we only have folders, we can skip the check
-        // for files (tiles.json)
-        if element_path.is_dir() {
-
-            match mode {
-                Mode::Tiles => {
-                    final_json[element_name] = if folder_contains_folder(&element_path) {
-                        iterate_over_folder(json::object! {}, &element_path, mode, None, None)
-                    } else {
-                        create_file_list(&element_path)
-                    };
-                },
-                Mode::Files => {
-
-                    if folder_contains_folder(&element_path) {
-                        iterate_over_folder(
-                            final_json.clone(),
-                            &element_path, mode,
-                            folder_part_to_remove.clone(),
-                            file_to_write_to.clone()
-                        );
-                    } else {
-                        create_required_files(
-                            &element_path,
-                            folder_part_to_remove.clone().unwrap(),
-                            file_to_write_to.clone()
-                        );
-                    }
-                }
-            }
-        }
-    }
+pub fn hash_file(path: &Path) -> Option<String> {
+    let mut content: Vec<u8> = Vec::new();
+    File::open(path).ok()?.read_to_end(&mut content).ok()?;
 
-    final_json // Return the final json
+    Some(Sha256::digest(&content).iter().map(|byte| format!("{:02x}", byte)).collect())
 }
 
-/// # create_required_files
-/// Push the required files for the project in the array of the json.
+///
+/// # write_gpx_file
+/// Write a list of points as a single track GPX file.
 ///
 /// ## Arguments
-/// * `folder_path` - The path of the folder to iterate over.
-/// * `folder_part_to_remove` - The part of the folder path to remove.
-/// * `file_to_write_to` - The file to write to.
+/// * `points(&[Point])` - The points of the track
+/// * `track_name(&str)` - The name of the track
+/// * `track_description(Option<String>)` - The description of the track
+/// * `file_destination(&Path)` - The path to the GPX file
 ///
 /// ## Returns
-/// * `String` - The file name.
-fn create_required_files(
-    folder_path: &Path,
-    folder_part_to_remove: String,
-    file_to_write_to: Option<&PathBuf>,
-) {
-    // Check if the folder exists
-    if !folder_path.exists() {
-        panic!("The folder {:?} does not exist - ALED", folder_path);
+/// * `bool` - True if the file was saved, false otherwise
+#[allow(dead_code)]
+pub fn write_gpx_file(
+    points: &[Point],
+    track_name: &str,
+    track_description: Option<String>,
+    file_destination: &Path,
+) -> bool {
+    let mut segment = TrackSegment::new();
+    for point in points {
+        let mut waypoint = Waypoint::new(geo_types::Point::new(point.coords.lon, point.coords.lat));
+        waypoint.elevation = point.elevation;
+        waypoint.name = point.name.clone();
+        waypoint.description = point.description.clone();
+
+        segment.points.push(waypoint);
     }
 
+    let mut track = Track::new();
+    track.name = Some(track_name.to_string());
+    track.description = track_description;
+    track.segments.push(segment);
 
-    // Iterate over the folder.
-    // If the element is a file, add it to the final json.
-    // If the element is a folder, add it to the final json and iterate over it.
-    for element in read_dir(&folder_path).unwrap() {
-        if !element.is_ok() {
-            // If the element is not ok, skip it
-            continue;
-        }
-
-        let element = element.unwrap(); // Get the element
-        let element_path = element.path(); // Get the path of the element
-
-        if element_path.is_file() {
-            // add the file to the json array,
-            // the required is of the form:
-            // require(../../assets/Chupaca/12/1191/2186.png)
-            // let file_path = format!("require({}{
-            // remove the folder_part_to_remove from the element_path
-            let element_path = element_path.to_str().unwrap().replace(&folder_part_to_remove, "");
-
-            let final_string = format!("require('{}{}'),", PATH_PREFIX, element_path);
+    let gpx = Gpx {
+        version: GpxVersion::Gpx11,
+        creator: Some("coords-tool-valpinera".to_string()),
+        tracks: vec![track],
+        ..Default::default()
+    };
 
-            // write the string to the file
-            let mut file_ref = OpenOptions::new().append(true).open(file_to_write_to.unwrap()).unwrap();
-
-            if let Some(file_to_write_to) = file_to_write_to {
-                match file_ref.write_all(final_string.as_bytes()) {
-                    Ok(_) => (),
-                    Err(e) => panic!("Could not write to file: {}", e),
-                }
-            }
+    match write(&gpx, File::create(file_destination).unwrap()) {
+        Ok(_) => {
+            println!("Successfully saved to: {}", file_destination.display());
+            true
+        }
+        Err(error) => {
+            println!("Error: {}", error);
+            false
         }
     }
-
 }
 
-///
-/// # create_file_list
-/// Creates a json with the files into a list.
+/// # load_from_json
+/// Load a HashMap<&str, HashMap<&str, Vec<(usize, usize)>>> from a JSON file.
 ///
 /// ## Arguments
-/// * `folder_path` - The path of the folder to iterate over.
+/// * `file_path(&PathBuf)` - The path to the JSON file
 ///
 /// ## Returns
-/// * `json::JsonValue` - The json with the files and folders.
-pub fn create_file_list(folder_path: &Path) -> JsonValue {
-    // Check if the folder exists
-    if !folder_path.exists() {
-        panic!("The folder {:?} does not exist - ALED", folder_path);
-    }
+/// * `Result<HashMap<&str, HashMap<&str, Vec<(usize, usize)>>>` - A Result containing the loaded HashMap or an error
+#[allow(dead_code)]
+pub fn load_from_json(
+    file_path: &PathBuf,
+) -> FileCoordsHM {
+    // Open the file
+    let mut file = File::open(file_path).expect("The file could not be opened");
+
+    // Read the contents of the file into a string
+    let mut json_string = String::new();
+    file.read_to_string(&mut json_string).expect("The file could not be read");
 
-    let mut final_json: JsonValue = json::array! [];
+    // Parse the JSON string into a HashMap
+    let result: FileCoordsHM =
+        serde_json::from_str(&json_string).expect("The JSON string could not be parsed");
 
-    // Iterate over the folder.
-    // If the element is a file, add it to the final json.
-    // If the element is a folder, add it to the final json and iterate over it.
-    for element in read_dir(&folder_path).unwrap() {
-        if !element.is_ok() {
-            // If the element is not ok, skip it
-            continue;
-        }
 
-        let element = element.unwrap(); // Get the element
-        let element_path = element.path(); // Get the path of the element
-        let element_name = element_path.file_name().unwrap().to_str().unwrap(); // Get the name of the element
+    result
+}
 
-        if element_path.is_file() {
-            final_json.push(element_name).expect("TODO: ALEDAOSSAZED");
-        }
-    }
 
-    final_json // Return the final json
+#[test]
+fn test_file_stem() {
+    assert_eq!(file_stem("Balcon2-1.gpx"), "Balcon2-1");
+    assert_eq!(file_stem("a.b.gpx"), "a.b");
+    assert_eq!(file_stem("track"), "track");
 }
+
 // END FUNCTIONS =======================================================================================  END FUNCTIONS
 
 //
@@ -12,9 +12,12 @@ mod gpx_utils;
 #[path = "file_utils.rs"]
 mod file_utils;
 
+#[path = "utils.rs"]
+mod utils;
+
 use std::path::PathBuf;
 use crate::file_utils::{file_name_to_path_buf, read_gpx_file};
-use crate::gpx_utils::{calc_distance, Coord};
+use crate::gpx_utils::{calc_distance, Coord, DistanceUnit};
 // END IMPORTS ==========================================================================================   END IMPORTS
 
 // VARIABLES ================================================================================================ VARIABLE
@@ -46,7 +49,7 @@ fn find_closests_points(file_name: &str, point: Coord, nb_points: usize) -> Vec<
     let mut indexes_distance: Vec<(usize, f64)> = coords
         .iter()
         .enumerate()
-        .map(|(i, coord)| (i, calc_distance(point, *coord, Some(true))))
+        .map(|(i, coord)| (i, calc_distance(point, *coord, DistanceUnit::Meters)))
         .collect();
 
     indexes_distance
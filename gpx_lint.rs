@@ -0,0 +1,114 @@
+/// # gpx_lint.rs
+/// This binary checks GPX files for problems (duplicated points, impossible jumps,
+/// missing elevation, empty segments, ...).
+/// It exits with a non-zero code if an error was found, so it can be used as a pre-commit check.
+///
+/// Arguments:
+/// * `--json` (optional) - Print the issues as JSON instead of text.
+/// * files (optional) - The GPX files to check, all the files of the 'assets' folder by default.
+///
+/// ## Author
+/// * Tom Planche - <github.com/tomPlanche>
+///
+/// ## Example
+/// >> cargo run --bin gpx_lint assets/Balcon2-1.gpx
+/// Balcon2-1.gpx
+///   warning [missing_elevation]: 501 of 501 points have no elevation
+
+// IMPORTS ===================================================================================================  IMPORTS
+#[path = "gpx_utils.rs"]
+mod gpx_utils;
+
+#[path = "file_utils.rs"]
+mod file_utils;
+
+#[path = "utils.rs"]
+mod utils;
+
+#[path = "lint_utils.rs"]
+mod lint_utils;
+
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::process::exit;
+
+use ansi_term::Colour::{Green, Red, Yellow};
+
+use crate::file_utils::{look_4_files, read_file_name, read_gpx_document};
+use crate::lint_utils::{lint_gpx, LintIssue, Severity};
+// END IMPORTS ==========================================================================================   END IMPORTS
+
+// CODE ========================================================================================================= CODE
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+
+    let as_json: bool = args.iter().any(|arg| arg == "--json");
+    let mut files: Vec<PathBuf> = args
+        .iter()
+        .filter(|arg| *arg != "--json")
+        .map(PathBuf::from)
+        .collect();
+
+    if files.is_empty() {
+        files = look_4_files();
+    }
+
+    // Map of file names to their issues, sorted by name
+    let mut issues_map: BTreeMap<String, Vec<LintIssue>> = BTreeMap::new();
+
+    for file in &files {
+        let file_name: String = match read_file_name(file) {
+            Some(file_name) => file_name,
+            None => panic!("Could not read the file name of {:?}", file),
+        };
+
+        let issues: Vec<LintIssue> = match read_gpx_document(file) {
+            Ok(gpx) => lint_gpx(&gpx),
+            Err(error) => vec![LintIssue {
+                severity: Severity::Error,
+                rule: "invalid_gpx".to_string(),
+                message: error,
+                track: None,
+                segment: None,
+                point: None,
+            }],
+        };
+
+        issues_map.insert(file_name, issues);
+    }
+
+    if as_json {
+        println!("{}", serde_json::to_string_pretty(&issues_map).unwrap());
+    } else {
+        for (file_name, issues) in &issues_map {
+            println!("{}", file_name);
+
+            if issues.is_empty() {
+                println!("  {}", Green.paint("ok"));
+            }
+
+            for issue in issues {
+                let severity = match issue.severity {
+                    Severity::Warning => Yellow.paint(issue.severity.to_string()),
+                    Severity::Error => Red.paint(issue.severity.to_string()),
+                };
+
+                println!("  {} {}", severity, issue);
+            }
+        }
+    }
+
+    let has_errors: bool = issues_map
+        .values()
+        .flatten()
+        .any(|issue| issue.severity == Severity::Error);
+
+    if has_errors {
+        exit(1);
+    }
+}
+// END CODE =======================================================================================  END COMPONENT
+
+//
+// * End of file /gpx_lint.rs
+//
@@ -1,5 +1,9 @@
 /// # gpx_to_json.rs
+/// This binary converts the GPX files of the 'assets' folder into JSON files of Points.
 ///
+/// Arguments:
+/// * `--dem <folder>` (optional) - A folder of SRTM `.hgt` tiles used to fill the missing elevations.
+/// * files (optional) - The names of GPX files in the 'assets' folder, all of them by default.
 ///
 /// /// * Tom Planche - <github.com/tomPlanche>
 
@@ -10,12 +14,20 @@ mod gpx_utils;
 #[path = "file_utils.rs"]
 mod file_utils;
 
+#[path = "utils.rs"]
+mod utils;
+
+#[path = "dem_utils.rs"]
+mod dem_utils;
+
 use std::fs::File;
 use std::io::Write;
 use std::path::PathBuf;
-use file_utils::{file_name_to_path_buf, read_gpx_file};
-use crate::file_utils::look_4_files;
-use crate::gpx_utils::{Coord, Point};
+use ansi_term::Colour::Red;
+use file_utils::{file_name_to_path_buf, read_gpx_points};
+use crate::dem_utils::DemSampler;
+use crate::file_utils::{file_stem, look_4_files};
+use crate::gpx_utils::Point;
 
 // END IMPORTS ==========================================================================================   END IMPORTS
 
@@ -34,27 +46,31 @@ use crate::gpx_utils::{Coord, Point};
 /// ## Arguments
 /// * `file_name` - The name of the file to read from.
 /// * `file_destination` - The name of the file to write to.
+/// * `dem` - The DEM used to fill the missing elevations, if any.
 ///
 /// ## Returns
 /// * `bool` - True if the file was successfully saved, false otherwise.
-fn gpx_to_json(file_name: String, file_destination: String) -> bool {
+fn gpx_to_json(file_name: String, file_destination: String, mut dem: Option<&mut DemSampler>) -> bool {
     let path_buff_from_file: PathBuf = file_name_to_path_buf(&file_name);
 
-    let coords: Vec<Coord> = match read_gpx_file(&path_buff_from_file) {
-        Some(coords) => coords,
+    let gpx_points: Vec<Point> = match read_gpx_points(&path_buff_from_file) {
+        Some(points) => points,
         None => panic!("Could not read the file {:?}", file_name),
     };
 
-    let nb_points: usize = coords.len();
+    let nb_points: usize = gpx_points.len();
 
-    let points: Vec<Point> = coords
+    let points: Vec<Point> = gpx_points
         .iter()
         .enumerate()
-        .map(|(i, coord)| Point {
-            coords: *coord,
+        .map(|(i, point)| Point {
+            coords: point.coords,
             name: Some(format!("{}/{}", i, nb_points)),
             description: None,
-            elevation: None,
+            elevation: match (point.elevation, dem.as_mut()) {
+                (None, Some(dem)) => dem.elevation(point.coords),
+                (elevation, _) => elevation,
+            },
         })
         .collect();
 
@@ -73,7 +89,26 @@ fn gpx_to_json(file_name: String, file_destination: String) -> bool {
 }
 
 fn main() {
-    let gpx_files: Vec<PathBuf> = look_4_files();
+    let args: Vec<String> = std::env::args().collect();
+
+    let mut dem: Option<DemSampler> = None;
+    let mut gpx_files: Vec<PathBuf> = Vec::new();
+
+    let mut rest = args.iter().skip(1);
+    while let Some(arg) = rest.next() {
+        match arg.as_str() {
+            "--dem" => match rest.next() {
+                Some(folder) => dem = Some(DemSampler::new(PathBuf::from(folder))),
+                None => panic!("Please provide the DEM folder after --dem"),
+            },
+            other if other.ends_with(".gpx") => gpx_files.push(file_name_to_path_buf(other)),
+            other => panic!("Unknown argument {:?}", other),
+        }
+    }
+
+    if gpx_files.is_empty() {
+        gpx_files = look_4_files();
+    }
 
     for file in gpx_files {
         let file_name: String = match file.file_name() {
@@ -81,12 +116,15 @@ fn main() {
             None => panic!("Could not read the file name of {:?}", file),
         };
 
-        // remove the extension
-        let file_name_destination: String = file_name.split('.').collect::<Vec<&str>>()[0].to_string();
+        let file_destination: String = format!("./output/{}.json", file_stem(&file_name));
 
-        let file_destination: String = format!("./output/{}.json", file_name_destination);
+        gpx_to_json(file_name, file_destination, dem.as_mut());
+    }
 
-        gpx_to_json(file_name, file_destination);
+    if let Some(dem) = dem {
+        for tile in &dem.missing_tiles {
+            println!("{}", Red.paint(format!("Missing DEM tile: {}", dem.folder.join(tile).display())));
+        }
     }
 }
 // END CODE =======================================================================================  END COMPONENT
@@ -8,6 +8,7 @@
 // IMPORTS ===================================================================================================  IMPORTS
 use std::fmt::{Display, Formatter};
 use std::hash::Hash;
+use std::str::FromStr;
 
 use serde::{Deserialize, Serialize};
 // END IMPORTS ==========================================================================================   END IMPORTS
@@ -44,6 +45,65 @@ impl Display for Point {
     }
 }
 
+// Mean radius of the earth (in km), used by the spherical models.
+const EARTH_RADIUS: f64 = 6371.0;
+
+// WGS84 ellipsoid: semi-major axis (in meters), also the radius of the Web Mercator sphere, and flattening.
+#[allow(dead_code)]
+pub const WGS84_A: f64 = 6_378_137.0;
+#[allow(dead_code)]
+pub const WGS84_F: f64 = 1.0 / 298.257_223_563;
+
+const VINCENTY_MAX_ITERATIONS: usize = 200;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DistanceModel {
+    // Sphere, the historical model of the tools
+    #[default]
+    Haversine,
+    // WGS84 ellipsoid, sub-millimetre but slower
+    Vincenty,
+    // Flat approximation, for short distances
+    Equirectangular,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DistanceUnit {
+    Meters,
+    Kilometers,
+}
+
+impl DistanceUnit {
+    ///
+    /// # convert_kilometers
+    /// Convert a distance in km to this unit.
+    pub fn convert_kilometers(self, kilometers: f64) -> f64 {
+        match self {
+            DistanceUnit::Meters => kilometers * 1000.0,
+            DistanceUnit::Kilometers => kilometers,
+        }
+    }
+}
+
+impl FromStr for DistanceModel {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "haversine" => Ok(DistanceModel::Haversine),
+            "vincenty" => Ok(DistanceModel::Vincenty),
+            "equirectangular" => Ok(DistanceModel::Equirectangular),
+            other => Err(format!("Unknown distance model {:?}, expected 'haversine', 'vincenty' or 'equirectangular'", other)),
+        }
+    }
+}
+
+// Two points closer than this (in meters) are considered common to two tracks.
+#[allow(dead_code)]
+pub const COMMON_COORDS_THRESHOLD: f64 = 10.0;
+
 // Hash in order to use as a key in a HashMap
 impl Hash for Coord {
     fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
@@ -66,23 +126,12 @@ impl Eq for Coord {}
 
 // FUNCTIONS ================================================================================================ FUNCTIONS
 ///
-/// # calc_distance
-/// Calculate the distance between two coordinates
-/// using the Haversine formula.
-///
-/// ## Arguments
-/// * `coord1(Coord)` - The first coordinate in decimal degrees.
-/// * `coord2(Coord)` - The second coordinate in decimal degrees.
-///
-/// ## Returns
-/// * `f64` - The distance between the two coordinates (in meters).
-#[allow(dead_code)]
-pub fn calc_distance(coord1: Coord, coord2: Coord, in_meter: Option<bool>) -> f64 {
+/// # haversine_distance
+/// Distance (in km) on a sphere of radius `EARTH_RADIUS`.
+fn haversine_distance(coord1: Coord, coord2: Coord) -> f64 {
     let Coord { lat: lat_1, lon: lon_1 } = coord1;
     let Coord { lat: lat_2, lon: lon_2 } = coord2;
 
-    const EARTH_RADIUS: f64 = 6371.0; // km
-
     let delta_lat: f64 = (lat_2 - lat_1).to_radians();
     let delta_lon: f64 = (lon_2 - lon_1).to_radians();
 
@@ -102,7 +151,282 @@ pub fn calc_distance(coord1: Coord, coord2: Coord, in_meter: Option<bool>) -> f6
         * a.sqrt()
         .atan2((1.0 - a).sqrt());
 
-    EARTH_RADIUS * c * if in_meter.unwrap_or(false) { 1000.0 } else { 1.0 }
+    EARTH_RADIUS * c
+}
+
+///
+/// # equirectangular_distance
+/// Distance (in km) with the equirectangular approximation,
+/// accurate for the short distances compared by the comparator and much cheaper than Haversine.
+fn equirectangular_distance(coord1: Coord, coord2: Coord) -> f64 {
+    let x: f64 = (coord2.lon - coord1.lon).to_radians() * ((coord1.lat + coord2.lat) / 2.0).to_radians().cos();
+    let y: f64 = (coord2.lat - coord1.lat).to_radians();
+
+    EARTH_RADIUS * x.hypot(y)
+}
+
+///
+/// # vincenty_distance
+/// Distance (in km) on the WGS84 ellipsoid with the inverse Vincenty formula.
+/// None for nearly antipodal points, where the formula does not converge.
+fn vincenty_distance(coord1: Coord, coord2: Coord) -> Option<f64> {
+    let b: f64 = WGS84_A * (1.0 - WGS84_F);
+
+    let l: f64 = (coord2.lon - coord1.lon).to_radians();
+    let u_1: f64 = ((1.0 - WGS84_F) * coord1.lat.to_radians().tan()).atan();
+    let u_2: f64 = ((1.0 - WGS84_F) * coord2.lat.to_radians().tan()).atan();
+    let (sin_u_1, cos_u_1) = u_1.sin_cos();
+    let (sin_u_2, cos_u_2) = u_2.sin_cos();
+
+    let mut lambda: f64 = l;
+
+    for _ in 0..VINCENTY_MAX_ITERATIONS {
+        let (sin_lambda, cos_lambda) = lambda.sin_cos();
+
+        let sin_sigma: f64 = (cos_u_2 * sin_lambda).hypot(cos_u_1 * sin_u_2 - sin_u_1 * cos_u_2 * cos_lambda);
+        if sin_sigma == 0.0 {
+            // Same points
+            return Some(0.0);
+        }
+
+        let cos_sigma: f64 = sin_u_1 * sin_u_2 + cos_u_1 * cos_u_2 * cos_lambda;
+        let sigma: f64 = sin_sigma.atan2(cos_sigma);
+
+        let sin_alpha: f64 = cos_u_1 * cos_u_2 * sin_lambda / sin_sigma;
+        let cos_sq_alpha: f64 = 1.0 - sin_alpha * sin_alpha;
+        // 0 on the equator
+        let cos_2_sigma_m: f64 = if cos_sq_alpha == 0.0 {
+            0.0
+        } else {
+            cos_sigma - 2.0 * sin_u_1 * sin_u_2 / cos_sq_alpha
+        };
+
+        let c: f64 = WGS84_F / 16.0 * cos_sq_alpha * (4.0 + WGS84_F * (4.0 - 3.0 * cos_sq_alpha));
+        let previous_lambda: f64 = lambda;
+        lambda = l
+            + (1.0 - c)
+                * WGS84_F
+                * sin_alpha
+                * (sigma + c * sin_sigma * (cos_2_sigma_m + c * cos_sigma * (-1.0 + 2.0 * cos_2_sigma_m * cos_2_sigma_m)));
+
+        if (lambda - previous_lambda).abs() < 1e-12 {
+            let u_sq: f64 = cos_sq_alpha * (WGS84_A * WGS84_A - b * b) / (b * b);
+            let big_a: f64 = 1.0 + u_sq / 16384.0 * (4096.0 + u_sq * (-768.0 + u_sq * (320.0 - 175.0 * u_sq)));
+            let big_b: f64 = u_sq / 1024.0 * (256.0 + u_sq * (-128.0 + u_sq * (74.0 - 47.0 * u_sq)));
+            let delta_sigma: f64 = big_b
+                * sin_sigma
+                * (cos_2_sigma_m
+                    + big_b / 4.0
+                        * (cos_sigma * (-1.0 + 2.0 * cos_2_sigma_m * cos_2_sigma_m)
+                            - big_b / 6.0
+                                * cos_2_sigma_m
+                                * (-3.0 + 4.0 * sin_sigma * sin_sigma)
+                                * (-3.0 + 4.0 * cos_2_sigma_m * cos_2_sigma_m)));
+
+            return Some(b * big_a * (sigma - delta_sigma) / 1000.0);
+        }
+    }
+
+    None
+}
+
+///
+/// # calc_distance_with
+/// Calculate the distance between two coordinates with a given model.
+/// Vincenty falls back on Haversine for nearly antipodal points, where it does not converge.
+///
+/// ## Arguments
+/// * `coord1(Coord)` - The first coordinate in decimal degrees.
+/// * `coord2(Coord)` - The second coordinate in decimal degrees.
+/// * `model(DistanceModel)` - The model asked for.
+/// * `unit(DistanceUnit)` - The unit of the result.
+///
+/// ## Returns
+/// * `(f64, DistanceModel)` - The distance between the two coordinates and the model actually used.
+#[allow(dead_code)]
+pub fn calc_distance_with(coord1: Coord, coord2: Coord, model: DistanceModel, unit: DistanceUnit) -> (f64, DistanceModel) {
+    let (kilometers, model): (f64, DistanceModel) = match model {
+        DistanceModel::Haversine => (haversine_distance(coord1, coord2), model),
+        DistanceModel::Vincenty => match vincenty_distance(coord1, coord2) {
+            Some(kilometers) => (kilometers, model),
+            None => (haversine_distance(coord1, coord2), DistanceModel::Haversine),
+        },
+        DistanceModel::Equirectangular => (equirectangular_distance(coord1, coord2), model),
+    };
+
+    (unit.convert_kilometers(kilometers), model)
+}
+
+///
+/// # calc_distance
+/// Calculate the distance between two coordinates
+/// using the Haversine formula.
+///
+/// ## Arguments
+/// * `coord1(Coord)` - The first coordinate in decimal degrees.
+/// * `coord2(Coord)` - The second coordinate in decimal degrees.
+/// * `unit(DistanceUnit)` - The unit of the result.
+///
+/// ## Returns
+/// * `f64` - The distance between the two coordinates.
+#[allow(dead_code)]
+pub fn calc_distance(coord1: Coord, coord2: Coord, unit: DistanceUnit) -> f64 {
+    calc_distance_with(coord1, coord2, DistanceModel::Haversine, unit).0
+}
+
+///
+/// # calc_initial_bearing
+/// Calculate the initial bearing of the great circle from a coordinate to another.
+///
+/// ## Arguments
+/// * `coord1(Coord)` - The start coordinate in decimal degrees.
+/// * `coord2(Coord)` - The end coordinate in decimal degrees.
+///
+/// ## Returns
+/// * `f64` - The bearing (in degrees clockwise from the north, between 0 and 360).
+#[allow(dead_code)]
+pub fn calc_initial_bearing(coord1: Coord, coord2: Coord) -> f64 {
+    let lat_1: f64 = coord1.lat.to_radians();
+    let lat_2: f64 = coord2.lat.to_radians();
+    let delta_lon: f64 = (coord2.lon - coord1.lon).to_radians();
+
+    let y: f64 = delta_lon.sin() * lat_2.cos();
+    let x: f64 = lat_1.cos() * lat_2.sin() - lat_1.sin() * lat_2.cos() * delta_lon.cos();
+
+    y.atan2(x).to_degrees().rem_euclid(360.0)
+}
+
+///
+/// # calc_final_bearing
+/// Calculate the bearing of the great circle when arriving at the end coordinate.
+///
+/// ## Arguments
+/// * `coord1(Coord)` - The start coordinate in decimal degrees.
+/// * `coord2(Coord)` - The end coordinate in decimal degrees.
+///
+/// ## Returns
+/// * `f64` - The bearing (in degrees clockwise from the north, between 0 and 360).
+#[allow(dead_code)]
+pub fn calc_final_bearing(coord1: Coord, coord2: Coord) -> f64 {
+    (calc_initial_bearing(coord2, coord1) + 180.0).rem_euclid(360.0)
+}
+
+///
+/// # calc_destination
+/// Calculate the coordinate reached from a start coordinate following a great circle.
+///
+/// ## Arguments
+/// * `start(Coord)` - The start coordinate in decimal degrees.
+/// * `bearing(f64)` - The initial bearing (in degrees clockwise from the north).
+/// * `distance(f64)` - The distance to travel (in meters).
+///
+/// ## Returns
+/// * `Coord` - The destination, with a longitude between -180 and 180.
+#[allow(dead_code)]
+pub fn calc_destination(start: Coord, bearing: f64, distance: f64) -> Coord {
+    let angular_distance: f64 = distance / 1000.0 / EARTH_RADIUS;
+    let bearing: f64 = bearing.to_radians();
+    let lat_1: f64 = start.lat.to_radians();
+    let lon_1: f64 = start.lon.to_radians();
+
+    let lat_2: f64 = (lat_1.sin() * angular_distance.cos() + lat_1.cos() * angular_distance.sin() * bearing.cos()).asin();
+    let lon_2: f64 = lon_1
+        + (bearing.sin() * angular_distance.sin() * lat_1.cos()).atan2(angular_distance.cos() - lat_1.sin() * lat_2.sin());
+
+    Coord {
+        lat: lat_2.to_degrees(),
+        lon: (lon_2.to_degrees() + 540.0).rem_euclid(360.0) - 180.0,
+    }
+}
+
+///
+/// # interpolate_coords
+/// Calculate the coordinate at a fraction of the great circle between two coordinates.
+/// Every great circle through antipodal coordinates joins them, the meridian of the start is followed then.
+///
+/// ## Arguments
+/// * `coord1(Coord)` - The start coordinate in decimal degrees.
+/// * `coord2(Coord)` - The end coordinate in decimal degrees.
+/// * `fraction(f64)` - The fraction of the way, 0 at the start and 1 at the end.
+///
+/// ## Returns
+/// * `Coord` - The interpolated coordinate.
+#[allow(dead_code)]
+pub fn interpolate_coords(coord1: Coord, coord2: Coord, fraction: f64) -> Coord {
+    let (lat_1, lon_1) = (coord1.lat.to_radians(), coord1.lon.to_radians());
+    let (lat_2, lon_2) = (coord2.lat.to_radians(), coord2.lon.to_radians());
+
+    let start: [f64; 3] = [lat_1.cos() * lon_1.cos(), lat_1.cos() * lon_1.sin(), lat_1.sin()];
+    let end: [f64; 3] = [lat_2.cos() * lon_2.cos(), lat_2.cos() * lon_2.sin(), lat_2.sin()];
+
+    // From the vectors rather than the haversine, which loses precision near the antipode
+    let cross: f64 = (start[1] * end[2] - start[2] * end[1])
+        .hypot(start[2] * end[0] - start[0] * end[2])
+        .hypot(start[0] * end[1] - start[1] * end[0]);
+    let dot: f64 = start[0] * end[0] + start[1] * end[1] + start[2] * end[2];
+    let angular_distance: f64 = cross.atan2(dot);
+
+    if angular_distance == 0.0 {
+        return coord1;
+    }
+    if angular_distance.sin() < 1e-12 {
+        return calc_destination(coord1, 0.0, fraction * angular_distance * EARTH_RADIUS * 1000.0);
+    }
+
+    let a: f64 = ((1.0 - fraction) * angular_distance).sin() / angular_distance.sin();
+    let b: f64 = (fraction * angular_distance).sin() / angular_distance.sin();
+
+    let x: f64 = a * start[0] + b * end[0];
+    let y: f64 = a * start[1] + b * end[1];
+    let z: f64 = a * start[2] + b * end[2];
+
+    Coord {
+        lat: z.atan2(x.hypot(y)).to_degrees(),
+        lon: y.atan2(x).to_degrees(),
+    }
+}
+
+///
+/// # calc_midpoint
+/// Calculate the midpoint of the great circle between two coordinates.
+///
+/// ## Arguments
+/// * `coord1(Coord)` - The first coordinate in decimal degrees.
+/// * `coord2(Coord)` - The second coordinate in decimal degrees.
+///
+/// ## Returns
+/// * `Coord` - The midpoint.
+#[allow(dead_code)]
+pub fn calc_midpoint(coord1: Coord, coord2: Coord) -> Coord {
+    interpolate_coords(coord1, coord2, 0.5)
+}
+
+///
+/// # find_common_indexes
+/// Find the pairs of points of two coordinate lists closer than a threshold.
+/// This is the comparison the comparator runs for each pair of files,
+/// hence the equirectangular approximation.
+///
+/// ## Arguments
+/// * `coords_1(&[Coord])` - The first list of coordinates.
+/// * `coords_2(&[Coord])` - The second list of coordinates.
+/// * `threshold(f64)` - The maximum distance (in meters).
+///
+/// ## Returns
+/// * `Vec<(usize, usize)>` - The pairs of indexes (index in `coords_1`, index in `coords_2`).
+#[allow(dead_code)]
+pub fn find_common_indexes(coords_1: &[Coord], coords_2: &[Coord], threshold: f64) -> Vec<(usize, usize)> {
+    let mut common_indexes: Vec<(usize, usize)> = Vec::new();
+
+    for (index_1, coord_1) in coords_1.iter().enumerate() {
+        for (index_2, coord_2) in coords_2.iter().enumerate() {
+            if calc_distance_with(*coord_1, *coord_2, DistanceModel::Equirectangular, DistanceUnit::Meters).0 < threshold {
+                common_indexes.push((index_1, index_2));
+            }
+        }
+    }
+
+    common_indexes
 }
 
 #[test]
@@ -117,8 +441,99 @@ fn test_calc_distance() {
         lon: 1.0,
     };
 
-    assert_eq!(calc_distance(coord1, coord2, None), 98.6835497563641);
-    assert_eq!(calc_distance(coord1, coord1, None), 0.0);
+    assert_eq!(calc_distance(coord1, coord2, DistanceUnit::Kilometers), 98.6835497563641);
+    assert_eq!(calc_distance(coord1, coord1, DistanceUnit::Kilometers), 0.0);
+}
+
+#[test]
+fn test_calc_distance_models() {
+    // Flinders Peak to Buninyong, the example of Vincenty's paper: 54972.271m on the ellipsoid
+    let flinders_peak = Coord {
+        lat: -(37.0 + 57.0 / 60.0 + 3.72030 / 3600.0),
+        lon: 144.0 + 25.0 / 60.0 + 29.52440 / 3600.0,
+    };
+    let buninyong = Coord {
+        lat: -(37.0 + 39.0 / 60.0 + 10.15610 / 3600.0),
+        lon: 143.0 + 55.0 / 60.0 + 35.38390 / 3600.0,
+    };
+
+    let (vincenty, model) = calc_distance_with(flinders_peak, buninyong, DistanceModel::Vincenty, DistanceUnit::Meters);
+    assert!((vincenty - 54_972.271).abs() < 0.001);
+    assert_eq!(model, DistanceModel::Vincenty);
+
+    // One degree of longitude on the equator: 111319.491m on the ellipsoid
+    let equator = (Coord { lat: 0.0, lon: 0.0 }, Coord { lat: 0.0, lon: 1.0 });
+    let (vincenty, _) = calc_distance_with(equator.0, equator.1, DistanceModel::Vincenty, DistanceUnit::Meters);
+    assert!((vincenty - 111_319.491).abs() < 0.001);
+
+    // Nearly antipodal, Vincenty does not converge and Haversine is used
+    let antipodal = (Coord { lat: 0.0, lon: 0.0 }, Coord { lat: 0.5, lon: 179.7 });
+    let (distance, model) = calc_distance_with(antipodal.0, antipodal.1, DistanceModel::Vincenty, DistanceUnit::Meters);
+    assert_eq!(model, DistanceModel::Haversine);
+    assert_eq!(distance, calc_distance(antipodal.0, antipodal.1, DistanceUnit::Meters));
+
+    // The sphere is within 0.5% of the ellipsoid, the approximations within 0.01% of each other on short distances
+    let haversine = calc_distance(flinders_peak, buninyong, DistanceUnit::Meters);
+    assert!((haversine - 54_972.271).abs() / 54_972.271 < 0.005);
+
+    let (coord_1, coord_2) = (Coord { lat: 42.68, lon: 0.08 }, Coord { lat: 42.681, lon: 0.0805 });
+    let (equirectangular, _) = calc_distance_with(coord_1, coord_2, DistanceModel::Equirectangular, DistanceUnit::Meters);
+    let haversine = calc_distance(coord_1, coord_2, DistanceUnit::Meters);
+    assert!((equirectangular - haversine).abs() / haversine < 0.0001);
+}
+
+#[test]
+fn test_calc_bearings() {
+    let origin = Coord { lat: 0.0, lon: 0.0 };
+
+    assert_eq!(calc_initial_bearing(origin, Coord { lat: 1.0, lon: 0.0 }), 0.0);
+    assert_eq!(calc_initial_bearing(origin, Coord { lat: 0.0, lon: 1.0 }), 90.0);
+    assert_eq!(calc_initial_bearing(origin, Coord { lat: 0.0, lon: -1.0 }), 270.0);
+
+    // Paris to New York leaves towards the north-west and arrives towards the south-west
+    let paris = Coord { lat: 48.8534, lon: 2.3488 };
+    let new_york = Coord { lat: 40.7128, lon: -74.006 };
+    assert!((calc_initial_bearing(paris, new_york) - 291.8).abs() < 0.1);
+    assert!((calc_final_bearing(paris, new_york) - 233.7).abs() < 0.1);
+}
+
+#[test]
+fn test_calc_destination_and_midpoint() {
+    let start = Coord { lat: 42.68, lon: 0.08 };
+    let end = Coord { lat: 42.69, lon: 0.2 };
+
+    let destination = calc_destination(start, calc_initial_bearing(start, end), calc_distance(start, end, DistanceUnit::Meters));
+    assert!(calc_distance(destination, end, DistanceUnit::Meters) < 0.001);
+
+    let midpoint = calc_midpoint(Coord { lat: 0.0, lon: 0.0 }, Coord { lat: 0.0, lon: 10.0 });
+    assert!((midpoint.lat - 0.0).abs() < 1e-9 && (midpoint.lon - 5.0).abs() < 1e-9);
+
+    let quarter = interpolate_coords(start, end, 0.25);
+    let quarter_distance = calc_distance(start, quarter, DistanceUnit::Meters);
+    assert!((quarter_distance - calc_distance(start, end, DistanceUnit::Meters) / 4.0).abs() < 0.001);
+}
+
+#[test]
+fn test_interpolate_antipodal_coords() {
+    let start = Coord { lat: 10.0, lon: 20.0 };
+    let antipode = Coord { lat: -10.0, lon: -160.0 };
+    let half_circumference = std::f64::consts::PI * EARTH_RADIUS;
+
+    // Any great circle joins them, the midpoint is a quarter of the Earth away from both
+    let midpoint = interpolate_coords(start, antipode, 0.5);
+    assert!(!midpoint.lat.is_nan() && !midpoint.lon.is_nan());
+    assert!((haversine_distance(start, midpoint) - half_circumference / 2.0).abs() < 0.001);
+    assert!((haversine_distance(midpoint, antipode) - half_circumference / 2.0).abs() < 0.001);
+
+    assert!(haversine_distance(interpolate_coords(start, antipode, 1.0), antipode) < 0.001);
+}
+
+#[test]
+fn test_find_common_indexes() {
+    let coords_1 = vec![Coord { lat: 42.0, lon: 0.0 }, Coord { lat: 42.001, lon: 0.0 }];
+    let coords_2 = vec![Coord { lat: 42.001, lon: 0.00005 }, Coord { lat: 43.0, lon: 0.0 }];
+
+    assert_eq!(find_common_indexes(&coords_1, &coords_2, COMMON_COORDS_THRESHOLD), vec![(1, 0)]);
 }
 
 #[test]
@@ -0,0 +1,358 @@
+///
+/// # grade_utils.rs
+/// This file contains the per-segment grade (slope) calculations,
+/// the difficulty bands and the steepest sustained sections of a track.
+///
+/// ## Author
+/// * Tom Planche - <github.com/tomPlanche>
+
+// IMPORTS ===================================================================================================  IMPORTS
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::elevation_utils::{smooth_elevations, ElevationFilter};
+use crate::gpx_utils::{calc_distance, Coord, DistanceUnit, Point};
+// END IMPORTS ==========================================================================================   END IMPORTS
+
+// VARIABLES ================================================================================================ VARIABLE
+// Upper bound (in %, exclusive) and label of each band, on the absolute grade.
+#[allow(dead_code)]
+pub const GRADE_BANDS: [(f64, &str); 3] = [
+    (10.0, "<10%"),
+    (20.0, "10-20%"),
+    (f64::INFINITY, ">20%"),
+];
+
+// Filter applied to the elevations before the grades. Unlike the hysteresis of the ascent calculations,
+// it keeps the elevation continuous: a step of a few meters over one segment would be read as a wall.
+#[allow(dead_code)]
+pub const GRADE_ELEVATION_FILTER: ElevationFilter = ElevationFilter::Median(5);
+
+// Minimum length (in meters) of a segment, shorter ones are merged with the next points
+// so that GPS jitter between close points does not give absurd grades.
+#[allow(dead_code)]
+pub const MIN_SEGMENT_LENGTH: f64 = 50.0;
+
+// Lengths (in meters) of the steepest sustained sections to look for.
+#[allow(dead_code)]
+pub const SUSTAINED_LENGTHS: [f64; 2] = [100.0, 500.0];
+
+// Grade between two consecutive points with an elevation
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct GradeSegment {
+    pub start: usize,
+    pub end: usize,
+    pub length: f64,
+    // In %, negative downhill
+    pub grade: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GradeBand {
+    pub label: String,
+    pub distance: f64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct SteepestSection {
+    pub min_length: f64,
+    pub start: usize,
+    pub end: usize,
+    pub length: f64,
+    pub grade: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GradeReport {
+    pub bands: Vec<GradeBand>,
+    // Steepest single segment (absolute, in %)
+    pub max_grade: f64,
+    pub steepest_sections: Vec<SteepestSection>,
+}
+// END VARIABLES =======================================================================================  END VARIABLES
+
+// FUNCTIONS ================================================================================================ FUNCTIONS
+///
+/// # grade_band
+/// Get the label of the band of a grade.
+///
+/// ## Arguments
+/// * `grade` - The grade (in %).
+///
+/// ## Returns
+/// * `&str` - The label of the band.
+#[allow(dead_code)]
+pub fn grade_band(grade: f64) -> &'static str {
+    GRADE_BANDS
+        .iter()
+        .find(|(upper, _)| grade.abs() < *upper)
+        .map_or(GRADE_BANDS[GRADE_BANDS.len() - 1].1, |(_, label)| label)
+}
+
+///
+/// # smoothed_elevations
+/// Get the elevations of the points with an elevation, smoothed with `GRADE_ELEVATION_FILTER`.
+///
+/// ## Returns
+/// * `Vec<(usize, f64)>` - The index of each point with an elevation and its smoothed elevation.
+fn smoothed_elevations(points: &[Point]) -> Vec<(usize, f64)> {
+    let indexes: Vec<usize> = (0..points.len()).filter(|index| points[*index].elevation.is_some()).collect();
+    let elevations: Vec<f64> = indexes.iter().filter_map(|index| points[*index].elevation).collect();
+
+    indexes
+        .into_iter()
+        .zip(smooth_elevations(&elevations, GRADE_ELEVATION_FILTER))
+        .collect()
+}
+
+///
+/// # calc_grades
+/// Calculate the grade between consecutive points with an elevation.
+/// The elevations are smoothed with `GRADE_ELEVATION_FILTER`, the points without elevation skipped
+/// and the segments shorter than `MIN_SEGMENT_LENGTH` extended to the next points.
+///
+/// ## Arguments
+/// * `points` - The points of the track.
+///
+/// ## Returns
+/// * `Vec<GradeSegment>` - The grade of each segment.
+#[allow(dead_code)]
+pub fn calc_grades(points: &[Point]) -> Vec<GradeSegment> {
+    let elevations = smoothed_elevations(points);
+    let mut segments: Vec<GradeSegment> = Vec::new();
+
+    let Some(&(mut start, mut start_elevation)) = elevations.first() else {
+        return segments;
+    };
+    let mut length: f64 = 0.0;
+    let mut previous: usize = start;
+
+    for &(index, elevation) in &elevations[1..] {
+        length += points[previous..=index]
+            .windows(2)
+            .map(|pair| calc_distance(pair[0].coords, pair[1].coords, DistanceUnit::Meters))
+            .sum::<f64>();
+        previous = index;
+
+        if length < MIN_SEGMENT_LENGTH {
+            continue;
+        }
+
+        segments.push(GradeSegment {
+            start,
+            end: index,
+            length,
+            grade: (elevation - start_elevation) / length * 100.0,
+        });
+
+        (start, start_elevation, length) = (index, elevation, 0.0);
+    }
+
+    segments
+}
+
+///
+/// # find_steepest_section
+/// Find the section of at least `min_length` with the steepest average grade.
+///
+/// ## Arguments
+/// * `segments` - The grade segments of the track.
+/// * `min_length` - The minimum length of the section (in meters).
+///
+/// ## Returns
+/// * `Option<SteepestSection>` - The steepest section, None if the track is shorter than `min_length`.
+#[allow(dead_code)]
+pub fn find_steepest_section(segments: &[GradeSegment], min_length: f64) -> Option<SteepestSection> {
+    let mut steepest: Option<SteepestSection> = None;
+
+    // Sliding window over the segments: for each first segment, the fewest segments reaching min_length
+    let mut last: usize = 0;
+    let mut length: f64 = 0.0;
+    let mut climb: f64 = 0.0;
+
+    for first in 0..segments.len() {
+        while last < segments.len() && length < min_length {
+            length += segments[last].length;
+            climb += segments[last].grade * segments[last].length / 100.0;
+            last += 1;
+        }
+
+        if length < min_length {
+            break;
+        }
+
+        let grade = climb / length * 100.0;
+        if steepest.is_none_or(|section| grade.abs() > section.grade.abs()) {
+            steepest = Some(SteepestSection {
+                min_length,
+                start: segments[first].start,
+                end: segments[last - 1].end,
+                length,
+                grade,
+            });
+        }
+
+        length -= segments[first].length;
+        climb -= segments[first].grade * segments[first].length / 100.0;
+    }
+
+    steepest
+}
+
+///
+/// # calc_grade_report
+/// Calculate the distance in each grade band, the max grade and the steepest sustained sections.
+///
+/// ## Arguments
+/// * `points` - The points of the track.
+///
+/// ## Returns
+/// * `GradeReport` - The grade report of the track.
+#[allow(dead_code)]
+pub fn calc_grade_report(points: &[Point]) -> GradeReport {
+    let segments = calc_grades(points);
+
+    let bands: Vec<GradeBand> = GRADE_BANDS
+        .iter()
+        .map(|(_, label)| GradeBand {
+            label: label.to_string(),
+            distance: segments
+                .iter()
+                .filter(|segment| grade_band(segment.grade) == *label)
+                .map(|segment| segment.length)
+                .sum(),
+        })
+        .collect();
+
+    GradeReport {
+        bands,
+        max_grade: segments.iter().map(|segment| segment.grade.abs()).fold(0.0, f64::max),
+        steepest_sections: SUSTAINED_LENGTHS
+            .iter()
+            .filter_map(|min_length| find_steepest_section(&segments, *min_length))
+            .collect(),
+    }
+}
+
+///
+/// # grades_to_geojson
+/// Convert the grade segments to a GeoJSON FeatureCollection,
+/// consecutive segments of the same band being merged in a single `LineString`.
+///
+/// ## Arguments
+/// * `points` - The points of the track.
+/// * `segments` - The grade segments of the track.
+///
+/// ## Returns
+/// * `Value` - The GeoJSON FeatureCollection, with the grade and band of each line.
+#[allow(dead_code)]
+pub fn grades_to_geojson(points: &[Point], segments: &[GradeSegment]) -> Value {
+    let mut features: Vec<Value> = Vec::new();
+    let mut group: Vec<&GradeSegment> = Vec::new();
+
+    let mut flush = |group: &mut Vec<&GradeSegment>| {
+        if group.is_empty() {
+            return;
+        }
+
+        let length: f64 = group.iter().map(|segment| segment.length).sum();
+        let grade: f64 = group.iter().map(|segment| segment.grade * segment.length).sum::<f64>() / length;
+        let coords: Vec<Coord> = points[group[0].start..=group[group.len() - 1].end]
+            .iter()
+            .map(|point| point.coords)
+            .collect();
+
+        features.push(json!({
+            "type": "Feature",
+            "geometry": {
+                "type": "LineString",
+                "coordinates": coords.iter().map(|coord| [coord.lon, coord.lat]).collect::<Vec<[f64; 2]>>(),
+            },
+            "properties": {
+                "grade": grade,
+                "band": grade_band(group[0].grade),
+                "length": length,
+            },
+        }));
+
+        group.clear();
+    };
+
+    for segment in segments {
+        if group.last().is_some_and(|last| grade_band(last.grade) != grade_band(segment.grade)) {
+            flush(&mut group);
+        }
+        group.push(segment);
+    }
+    flush(&mut group);
+
+    json!({
+        "type": "FeatureCollection",
+        "features": features,
+    })
+}
+
+#[cfg(test)]
+fn test_points(elevations: &[f64]) -> Vec<Point> {
+    // Points every ~100m going north
+    elevations
+        .iter()
+        .enumerate()
+        .map(|(i, elevation)| Point {
+            coords: Coord { lat: 42.0 + i as f64 * 0.0009, lon: 0.0 },
+            name: None,
+            description: None,
+            elevation: Some(*elevation),
+        })
+        .collect()
+}
+
+#[test]
+fn test_calc_grade_report() {
+    // Flat, then 15% for 200m, then 30% for 100m, then flat (the median filter keeps the steps between flat ends)
+    let points = test_points(&[1000.0, 1000.0, 1000.0, 1015.0, 1030.0, 1060.0, 1060.0, 1060.0]);
+    let report = calc_grade_report(&points);
+
+    let distances: Vec<f64> = report.bands.iter().map(|band| (band.distance / 100.0).round()).collect();
+    assert_eq!(distances, vec![4.0, 2.0, 1.0]);
+
+    assert!((report.max_grade - 30.0).abs() < 0.5);
+    assert_eq!((report.steepest_sections[0].start, report.steepest_sections[0].end), (4, 5));
+}
+
+#[test]
+fn test_calc_grade_report_descent() {
+    // Flat, then -25% for 200m, -15% for 200m, -5% for 100m, then flat: the bands are on the absolute grade
+    let points = test_points(&[1100.0, 1100.0, 1100.0, 1075.0, 1050.0, 1035.0, 1020.0, 1015.0, 1015.0, 1015.0]);
+    let report = calc_grade_report(&points);
+
+    let distances: Vec<f64> = report.bands.iter().map(|band| (band.distance / 100.0).round()).collect();
+    assert_eq!(distances, vec![5.0, 2.0, 2.0]);
+
+    assert!((report.max_grade - 25.0).abs() < 0.5);
+    assert!(report.steepest_sections[0].grade < 0.0);
+}
+
+#[test]
+fn test_calc_grade_report_spike() {
+    // A steady 2% climb with a 20m spike on one point, which must not be read as a wall
+    let points = test_points(&[1000.0, 1002.0, 1004.0, 1006.0, 1028.0, 1010.0, 1012.0, 1014.0, 1016.0]);
+    let report = calc_grade_report(&points);
+
+    let total: f64 = report.bands.iter().map(|band| band.distance).sum();
+    assert_eq!(report.bands[0].distance, total);
+    assert!(report.max_grade < 10.0);
+}
+
+#[test]
+fn test_grade_band() {
+    assert_eq!(grade_band(5.0), "<10%");
+    assert_eq!(grade_band(-15.0), "10-20%");
+    assert_eq!(grade_band(25.0), ">20%");
+}
+
+// END FUNCTIONS =======================================================================================  END FUNCTIONS
+
+//
+// * End of file grade_utils.rs
+//
@@ -0,0 +1,419 @@
+///
+/// # graph_utils.rs
+/// This file contains the functions used to build the trail network graph
+/// from the overlaps found by the comparator.
+///
+/// Every trail is cut where the set of trails sharing its path changes.
+/// The cut points become junction nodes and the pieces between them become edges,
+/// a shared section only being kept once (on the trail whose name comes first).
+///
+/// ## Author
+/// * Tom Planche - <github.com/tomPlanche>
+
+// IMPORTS ===================================================================================================  IMPORTS
+use std::collections::{BTreeSet, HashMap};
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::elevation_utils::{calc_ascent_descent, DEFAULT_ELEVATION_FILTER};
+use crate::gpx_utils::{calc_distance, Coord, DistanceUnit, Point};
+use crate::utils::FileCoordsHM;
+// END IMPORTS ==========================================================================================   END IMPORTS
+
+// VARIABLES ================================================================================================ VARIABLE
+// Two cut points closer than this (in meters) are merged into the same junction.
+// Twice the comparator threshold, since both trails are only known to be within 10m of each other.
+#[allow(dead_code)]
+pub const JUNCTION_RADIUS: f64 = 20.0;
+
+// Runs of points shorter than this are considered comparator noise
+// and take the sharing set of the run before them.
+#[allow(dead_code)]
+pub const MIN_RUN_POINTS: usize = 3;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GraphNode {
+    pub id: usize,
+    pub coords: Coord,
+    pub degree: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EdgeSource {
+    pub trail: String,
+    pub start: usize,
+    pub end: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GraphEdge {
+    pub id: usize,
+    pub from: usize,
+    pub to: usize,
+    pub length: f64,
+    pub ascent: f64,
+    pub descent: f64,
+    pub trails: Vec<String>,
+    pub source: EdgeSource,
+    pub coords: Vec<Coord>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TrailGraph {
+    pub nodes: Vec<GraphNode>,
+    pub edges: Vec<GraphEdge>,
+}
+// END VARIABLES =======================================================================================  END VARIABLES
+
+// FUNCTIONS ================================================================================================ FUNCTIONS
+///
+/// # sharing_sets
+/// For each point of each trail, compute the set of other trails passing there.
+///
+/// ## Arguments
+/// * `tracks` - The points of each trail.
+/// * `overlaps` - The overlaps found by the comparator.
+///
+/// ## Returns
+/// * `HashMap<String, Vec<BTreeSet<String>>>` - The sharing sets of each trail.
+#[allow(dead_code)]
+pub fn sharing_sets(
+    tracks: &HashMap<String, Vec<Point>>,
+    overlaps: &FileCoordsHM,
+) -> HashMap<String, Vec<BTreeSet<String>>> {
+    let mut sets: HashMap<String, Vec<BTreeSet<String>>> = tracks
+        .iter()
+        .map(|(name, points)| (name.clone(), vec![BTreeSet::new(); points.len()]))
+        .collect();
+
+    for (file_1, others) in overlaps {
+        for (file_2, pairs) in others {
+            if !sets.contains_key(file_1) || !sets.contains_key(file_2) {
+                continue;
+            }
+
+            for (index_1, index_2) in pairs {
+                if let Some(set) = sets.get_mut(file_1).unwrap().get_mut(*index_1) {
+                    set.insert(file_2.clone());
+                }
+                if let Some(set) = sets.get_mut(file_2).unwrap().get_mut(*index_2) {
+                    set.insert(file_1.clone());
+                }
+            }
+        }
+    }
+
+    sets
+}
+
+///
+/// # find_runs
+/// Split a list of sharing sets into runs of identical sets.
+/// Runs shorter than `min_run` are merged into the previous one.
+///
+/// ## Arguments
+/// * `sets` - The sharing set of each point of a trail.
+/// * `min_run` - The minimum number of points of a run.
+///
+/// ## Returns
+/// * `Vec<(usize, BTreeSet<String>)>` - The start index and sharing set of each run.
+#[allow(dead_code)]
+pub fn find_runs(sets: &[BTreeSet<String>], min_run: usize) -> Vec<(usize, BTreeSet<String>)> {
+    let mut runs: Vec<(usize, BTreeSet<String>)> = Vec::new();
+
+    for (index, set) in sets.iter().enumerate() {
+        match runs.last() {
+            Some((_, last_set)) if last_set == set => (),
+            _ => runs.push((index, set.clone())),
+        }
+    }
+
+    // Merge the runs that are too short into the previous one
+    let mut merged: Vec<(usize, BTreeSet<String>)> = Vec::new();
+    for (position, (start, set)) in runs.iter().enumerate() {
+        let end = runs.get(position + 1).map_or(sets.len(), |(next_start, _)| *next_start);
+
+        match merged.last() {
+            Some((_, last_set)) if last_set == set => (),
+            Some(_) if end - start < min_run => (),
+            _ => merged.push((*start, set.clone())),
+        }
+    }
+
+    merged
+}
+
+///
+/// # get_or_create_node
+/// Find the node within `JUNCTION_RADIUS` of a coordinate, or create it.
+///
+/// ## Arguments
+/// * `nodes` - The nodes of the graph.
+/// * `coords` - The coordinate of the node.
+///
+/// ## Returns
+/// * `usize` - The id of the node.
+fn get_or_create_node(nodes: &mut Vec<GraphNode>, coords: Coord) -> usize {
+    let closest = nodes
+        .iter()
+        .map(|node| (node.id, calc_distance(node.coords, coords, DistanceUnit::Meters)))
+        .filter(|(_, distance)| *distance < JUNCTION_RADIUS)
+        .min_by(|(_, dist_1), (_, dist_2)| dist_1.partial_cmp(dist_2).unwrap());
+
+    if let Some((id, _)) = closest {
+        return id;
+    }
+
+    let id = nodes.len();
+    nodes.push(GraphNode { id, coords, degree: 0 });
+
+    id
+}
+
+///
+/// # build_trail_graph
+/// Build the trail network graph.
+///
+/// ## Arguments
+/// * `tracks` - The points of each trail.
+/// * `overlaps` - The overlaps found by the comparator.
+///
+/// ## Returns
+/// * `TrailGraph` - The junction nodes and the unique path sections.
+#[allow(dead_code)]
+pub fn build_trail_graph(
+    tracks: &HashMap<String, Vec<Point>>,
+    overlaps: &FileCoordsHM,
+) -> TrailGraph {
+    let mut graph = TrailGraph::default();
+    let sets = sharing_sets(tracks, overlaps);
+
+    // Sort the trail names so the graph is the same between runs
+    let mut names: Vec<&String> = tracks.keys().collect();
+    names.sort();
+
+    // First pass: the sections owned by each trail, as (trail, start, end, shared with)
+    let mut sections: Vec<(&String, usize, usize, &BTreeSet<String>)> = Vec::new();
+    let mut runs_by_trail: HashMap<&String, Vec<(usize, BTreeSet<String>)>> = HashMap::new();
+
+    for name in &names {
+        if tracks[*name].len() >= 2 {
+            runs_by_trail.insert(name, find_runs(&sets[*name], MIN_RUN_POINTS));
+        }
+    }
+
+    for name in &names {
+        let Some(runs) = runs_by_trail.get(name) else {
+            continue;
+        };
+
+        // Cut between two runs on the side of the run shared by the most trails,
+        // so both trails get cut on a common point.
+        let mut cuts: Vec<usize> = vec![0];
+        for pair in runs.windows(2) {
+            let (next_start, next_set) = &pair[1];
+
+            cuts.push(if next_set.len() > pair[0].1.len() { *next_start } else { next_start - 1 });
+        }
+        cuts.push(tracks[*name].len() - 1);
+
+        for (position, (_, shared_with)) in runs.iter().enumerate() {
+            let (start, end) = (cuts[position], cuts[position + 1]);
+
+            // A shared section only belongs to the first trail (by name) sharing it
+            if shared_with.iter().next().is_some_and(|first| first < *name) || end <= start {
+                continue;
+            }
+
+            sections.push((name, start, end, shared_with));
+        }
+    }
+
+    // Junctions at both ends of every section
+    for (name, start, end, _) in &sections {
+        get_or_create_node(&mut graph.nodes, tracks[*name][*start].coords);
+        get_or_create_node(&mut graph.nodes, tracks[*name][*end].coords);
+    }
+
+    // Second pass: a trail can leave another one in the middle of a shared section,
+    // so the sections are also cut on the junctions lying on them.
+    for (name, start, end, shared_with) in sections {
+        let points = &tracks[name];
+        let own_nodes = [
+            get_or_create_node(&mut graph.nodes, points[start].coords),
+            get_or_create_node(&mut graph.nodes, points[end].coords),
+        ];
+
+        let mut splits: Vec<usize> = vec![start, end];
+        for node in graph.nodes.iter().filter(|node| !own_nodes.contains(&node.id)) {
+            let closest = (start + 1..end)
+                .map(|index| (index, calc_distance(node.coords, points[index].coords, DistanceUnit::Meters)))
+                .filter(|(_, distance)| *distance < JUNCTION_RADIUS)
+                .min_by(|(_, dist_1), (_, dist_2)| dist_1.partial_cmp(dist_2).unwrap());
+
+            if let Some((index, _)) = closest {
+                splits.push(index);
+            }
+        }
+        splits.sort();
+        splits.dedup();
+
+        for pair in splits.windows(2) {
+            let (start, end) = (pair[0], pair[1]);
+
+            let section = &points[start..=end];
+            let length: f64 = section
+                .windows(2)
+                .map(|pair| calc_distance(pair[0].coords, pair[1].coords, DistanceUnit::Meters))
+                .sum();
+
+            let from = get_or_create_node(&mut graph.nodes, section[0].coords);
+            let to = get_or_create_node(&mut graph.nodes, section[section.len() - 1].coords);
+
+            // Skip the sections collapsing on a single junction
+            if from == to && length < JUNCTION_RADIUS {
+                continue;
+            }
+
+            let (ascent, descent) = calc_ascent_descent(section, DEFAULT_ELEVATION_FILTER);
+
+            let mut trails: Vec<String> = vec![name.clone()];
+            trails.extend(shared_with.iter().cloned());
+
+            graph.edges.push(GraphEdge {
+                id: graph.edges.len(),
+                from,
+                to,
+                length,
+                ascent,
+                descent,
+                trails,
+                source: EdgeSource {
+                    trail: name.clone(),
+                    start,
+                    end,
+                },
+                coords: section.iter().map(|point| point.coords).collect(),
+            });
+        }
+    }
+
+    for edge in &graph.edges {
+        graph.nodes[edge.from].degree += 1;
+        graph.nodes[edge.to].degree += 1;
+    }
+
+    graph
+}
+
+///
+/// # graph_to_geojson
+/// Convert the graph to a GeoJSON FeatureCollection.
+/// Nodes are `Point` features and edges are `LineString` features.
+///
+/// ## Arguments
+/// * `graph` - The graph to convert.
+///
+/// ## Returns
+/// * `Value` - The GeoJSON FeatureCollection.
+#[allow(dead_code)]
+pub fn graph_to_geojson(graph: &TrailGraph) -> Value {
+    let mut features: Vec<Value> = Vec::new();
+
+    for node in &graph.nodes {
+        features.push(json!({
+            "type": "Feature",
+            "geometry": {
+                "type": "Point",
+                "coordinates": [node.coords.lon, node.coords.lat],
+            },
+            "properties": {
+                "id": node.id,
+                "degree": node.degree,
+            },
+        }));
+    }
+
+    for edge in &graph.edges {
+        features.push(json!({
+            "type": "Feature",
+            "geometry": {
+                "type": "LineString",
+                "coordinates": edge
+                    .coords
+                    .iter()
+                    .map(|coord| [coord.lon, coord.lat])
+                    .collect::<Vec<[f64; 2]>>(),
+            },
+            "properties": {
+                "id": edge.id,
+                "from": edge.from,
+                "to": edge.to,
+                "length": edge.length,
+                "ascent": edge.ascent,
+                "descent": edge.descent,
+                "trails": edge.trails,
+            },
+        }));
+    }
+
+    json!({
+        "type": "FeatureCollection",
+        "features": features,
+    })
+}
+
+#[cfg(test)]
+fn test_point(lat: f64, lon: f64, elevation: Option<f64>) -> Point {
+    Point {
+        coords: Coord { lat, lon },
+        name: None,
+        description: None,
+        elevation,
+    }
+}
+
+#[test]
+fn test_find_runs() {
+    let a: BTreeSet<String> = BTreeSet::new();
+    let b: BTreeSet<String> = BTreeSet::from(["b.gpx".to_string()]);
+
+    let sets = vec![a.clone(), a.clone(), a.clone(), b.clone(), a.clone(), a.clone(), b.clone(), b.clone(), b.clone()];
+
+    assert_eq!(find_runs(&sets, 1), vec![(0, a.clone()), (3, b.clone()), (4, a.clone()), (6, b.clone())]);
+    assert_eq!(find_runs(&sets, 3), vec![(0, a.clone()), (6, b.clone())]);
+}
+
+#[test]
+fn test_build_trail_graph() {
+    // 'a' goes north, 'b' shares its 3 last points then goes east
+    let a: Vec<Point> = (0..6).map(|i| test_point(42.0 + i as f64 * 0.001, 0.0, None)).collect();
+    let b: Vec<Point> = (3..6)
+        .map(|i| test_point(42.0 + i as f64 * 0.001, 0.0, None))
+        .chain((1..4).map(|i| test_point(42.005, i as f64 * 0.001, None)))
+        .collect();
+
+    let tracks: HashMap<String, Vec<Point>> = HashMap::from([
+        ("a.gpx".to_string(), a),
+        ("b.gpx".to_string(), b),
+    ]);
+    let overlaps: FileCoordsHM = HashMap::from([(
+        "a.gpx".to_string(),
+        HashMap::from([("b.gpx".to_string(), vec![(3, 0), (4, 1), (5, 2)])]),
+    )]);
+
+    let graph = build_trail_graph(&tracks, &overlaps);
+
+    // a only, a + b, b only
+    assert_eq!(graph.edges.len(), 3);
+    assert_eq!(graph.nodes.len(), 4);
+    assert_eq!(graph.edges[1].trails, vec!["a.gpx".to_string(), "b.gpx".to_string()]);
+    assert_eq!(graph.edges[1].to, graph.edges[2].from);
+}
+
+// END FUNCTIONS =======================================================================================  END FUNCTIONS
+
+//
+// * End of file graph_utils.rs
+//
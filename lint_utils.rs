@@ -0,0 +1,318 @@
+///
+/// # lint_utils.rs
+/// This file contains the checks run by the `gpx_lint` binary on a GPX file.
+///
+/// ## Author
+/// * Tom Planche - <github.com/tomPlanche>
+
+// IMPORTS ===================================================================================================  IMPORTS
+use std::fmt::{Display, Formatter};
+
+use gpx::{Gpx, Waypoint};
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+
+use crate::gpx_utils::{calc_distance, Coord, DistanceUnit};
+// END IMPORTS ==========================================================================================   END IMPORTS
+
+// VARIABLES ================================================================================================ VARIABLE
+// Two consecutive points further apart than this (in meters) are a gap in the recording.
+#[allow(dead_code)]
+pub const MAX_GAP: f64 = 200.0;
+
+// Two consecutive points further apart than this (in meters) can not be walked, it is a spike,
+// unless their timestamps show a pause or a lost signal at a walking speed.
+#[allow(dead_code)]
+pub const MAX_JUMP: f64 = 1000.0;
+
+// Faster than this (in m/s, 36 km/h) is not hiking anymore.
+#[allow(dead_code)]
+pub const MAX_SPEED: f64 = 10.0;
+
+// Two consecutive points recorded more than this (in seconds) apart are a gap in the recording.
+#[allow(dead_code)]
+pub const MAX_TIME_GAP: i64 = 10 * 60;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Severity {
+    Warning,
+    Error,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LintIssue {
+    pub severity: Severity,
+    pub rule: String,
+    pub message: String,
+    pub track: Option<usize>,
+    pub segment: Option<usize>,
+    pub point: Option<usize>,
+}
+
+impl Display for Severity {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Severity::Warning => write!(f, "warning"),
+            Severity::Error => write!(f, "error"),
+        }
+    }
+}
+
+impl Display for LintIssue {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "[{}]", self.rule)?;
+
+        if let Some(track) = self.track {
+            write!(f, " track {}", track)?;
+        }
+        if let Some(segment) = self.segment {
+            write!(f, ", segment {}", segment)?;
+        }
+        if let Some(point) = self.point {
+            write!(f, ", point {}", point)?;
+        }
+
+        write!(f, ": {}", self.message)
+    }
+}
+// END VARIABLES =======================================================================================  END VARIABLES
+
+// FUNCTIONS ================================================================================================ FUNCTIONS
+///
+/// # issue
+/// Shorthand to create a LintIssue.
+fn issue(
+    severity: Severity,
+    rule: &str,
+    message: String,
+    location: (Option<usize>, Option<usize>, Option<usize>),
+) -> LintIssue {
+    LintIssue {
+        severity,
+        rule: rule.to_string(),
+        message,
+        track: location.0,
+        segment: location.1,
+        point: location.2,
+    }
+}
+
+///
+/// # waypoint_coord
+/// Get the Coord of a GPX waypoint.
+fn waypoint_coord(waypoint: &Waypoint) -> Coord {
+    Coord {
+        lat: waypoint.point().y(),
+        lon: waypoint.point().x(),
+    }
+}
+
+///
+/// # lint_points
+/// Run the checks comparing each point of a segment with the previous one.
+///
+/// ## Arguments
+/// * `points` - The points of the segment.
+/// * `track` - The index of the track.
+/// * `segment` - The index of the segment.
+///
+/// ## Returns
+/// * `Vec<LintIssue>` - The issues found.
+fn lint_points(points: &[Waypoint], track: usize, segment: usize) -> Vec<LintIssue> {
+    let mut issues: Vec<LintIssue> = Vec::new();
+
+    for (index, point) in points.iter().enumerate() {
+        let location = (Some(track), Some(segment), Some(index));
+        let coord = waypoint_coord(point);
+
+        if !(-90.0..=90.0).contains(&coord.lat) || !(-180.0..=180.0).contains(&coord.lon) {
+            issues.push(issue(Severity::Error, "out_of_range", format!("{} is not a valid coordinate", coord), location));
+            continue;
+        }
+
+        if index == 0 {
+            continue;
+        }
+
+        let previous = &points[index - 1];
+        let distance = calc_distance(waypoint_coord(previous), coord, DistanceUnit::Meters);
+
+        let seconds: Option<i64> = match (previous.time, point.time) {
+            (Some(previous_time), Some(time)) => {
+                Some((OffsetDateTime::from(time) - OffsetDateTime::from(previous_time)).whole_seconds())
+            }
+            _ => None,
+        };
+        // None without timestamps, or when they do not go forward
+        let speed: Option<f64> = seconds.filter(|seconds| *seconds > 0).map(|seconds| distance / seconds as f64);
+        let jump: bool = distance > MAX_JUMP && speed.is_none_or(|speed| speed > MAX_SPEED);
+
+        if distance == 0.0 {
+            issues.push(issue(Severity::Warning, "duplicated_point", "same position as the previous point".to_string(), location));
+        } else if jump {
+            issues.push(issue(Severity::Error, "impossible_jump", format!("{:.0}m from the previous point", distance), location));
+        } else if distance > MAX_GAP {
+            issues.push(issue(Severity::Warning, "large_gap", format!("{:.0}m from the previous point", distance), location));
+        }
+
+        if let Some(seconds) = seconds {
+            if seconds > MAX_TIME_GAP {
+                issues.push(issue(Severity::Warning, "large_time_gap", format!("{}s after the previous point", seconds), location));
+            } else if !jump && seconds > 0 && distance / seconds as f64 > MAX_SPEED {
+                issues.push(issue(
+                    Severity::Error,
+                    "impossible_speed",
+                    format!("{:.1}m/s from the previous point", distance / seconds as f64),
+                    location,
+                ));
+            }
+        }
+    }
+
+    issues
+}
+
+///
+/// # lint_gpx
+/// Run all the checks on a GPX file.
+///
+/// ## Arguments
+/// * `gpx` - The parsed GPX file.
+///
+/// ## Returns
+/// * `Vec<LintIssue>` - The issues found.
+#[allow(dead_code)]
+pub fn lint_gpx(gpx: &Gpx) -> Vec<LintIssue> {
+    let mut issues: Vec<LintIssue> = Vec::new();
+    let mut nb_points: usize = 0;
+    let mut nb_without_elevation: usize = 0;
+    let mut length: f64 = 0.0;
+
+    if gpx.tracks.is_empty() {
+        issues.push(issue(Severity::Error, "no_track", "the file has no track".to_string(), (None, None, None)));
+    }
+
+    for (track_index, track) in gpx.tracks.iter().enumerate() {
+        if track.segments.is_empty() {
+            issues.push(issue(Severity::Error, "empty_track", "the track has no segment".to_string(), (Some(track_index), None, None)));
+        }
+
+        for (segment_index, segment) in track.segments.iter().enumerate() {
+            if segment.points.is_empty() {
+                issues.push(issue(
+                    Severity::Error,
+                    "empty_segment",
+                    "the segment has no point".to_string(),
+                    (Some(track_index), Some(segment_index), None),
+                ));
+                continue;
+            }
+
+            issues.extend(lint_points(&segment.points, track_index, segment_index));
+
+            nb_points += segment.points.len();
+            nb_without_elevation += segment.points.iter().filter(|point| point.elevation.is_none()).count();
+            length += segment
+                .points
+                .windows(2)
+                .map(|pair| calc_distance(waypoint_coord(&pair[0]), waypoint_coord(&pair[1]), DistanceUnit::Meters))
+                .sum::<f64>();
+        }
+    }
+
+    if nb_points > 0 && length == 0.0 {
+        issues.push(issue(Severity::Error, "zero_length", "the tracks have a length of 0m".to_string(), (None, None, None)));
+    }
+
+    if nb_without_elevation > 0 {
+        issues.push(issue(
+            Severity::Warning,
+            "missing_elevation",
+            format!("{} of {} points have no elevation", nb_without_elevation, nb_points),
+            (None, None, None),
+        ));
+    }
+
+    issues
+}
+
+#[cfg(test)]
+fn test_timed_waypoint(lat: f64, seconds: i64) -> Waypoint {
+    let mut waypoint = test_waypoint(lat, 0.0, Some(1000.0));
+    waypoint.time = Some((OffsetDateTime::UNIX_EPOCH + time::Duration::seconds(seconds)).into());
+
+    waypoint
+}
+
+#[cfg(test)]
+fn test_waypoint(lat: f64, lon: f64, elevation: Option<f64>) -> Waypoint {
+    let mut waypoint = Waypoint::new(geo_types::Point::new(lon, lat));
+    waypoint.elevation = elevation;
+
+    waypoint
+}
+
+#[test]
+fn test_lint_gpx() {
+    use gpx::{Track, TrackSegment};
+
+    let mut segment = TrackSegment::new();
+    segment.points = vec![
+        test_waypoint(42.0, 0.0, Some(1000.0)),
+        test_waypoint(42.0, 0.0, Some(1000.0)),
+        test_waypoint(42.003, 0.0, Some(1000.0)),
+        test_waypoint(42.1, 0.0, None),
+    ];
+
+    let mut track = Track::new();
+    track.segments = vec![segment, TrackSegment::new()];
+
+    let gpx = Gpx {
+        tracks: vec![track],
+        ..Default::default()
+    };
+
+    let rules: Vec<(Severity, String)> = lint_gpx(&gpx)
+        .into_iter()
+        .map(|issue| (issue.severity, issue.rule))
+        .collect();
+
+    assert_eq!(rules, vec![
+        (Severity::Warning, "duplicated_point".to_string()),
+        (Severity::Warning, "large_gap".to_string()),
+        (Severity::Error, "impossible_jump".to_string()),
+        (Severity::Error, "empty_segment".to_string()),
+        (Severity::Warning, "missing_elevation".to_string()),
+    ]);
+}
+
+#[test]
+fn test_lint_points_jump() {
+    let rules = |points: Vec<Waypoint>| -> Vec<(Severity, String)> {
+        lint_points(&points, 0, 0).into_iter().map(|issue| (issue.severity, issue.rule)).collect()
+    };
+
+    // 1.1km in 15 minutes, the signal was lost
+    assert_eq!(rules(vec![test_timed_waypoint(42.0, 0), test_timed_waypoint(42.01, 900)]), vec![
+        (Severity::Warning, "large_gap".to_string()),
+        (Severity::Warning, "large_time_gap".to_string()),
+    ]);
+    // 1.1km in 5 minutes, walking fast
+    assert_eq!(rules(vec![test_timed_waypoint(42.0, 0), test_timed_waypoint(42.01, 300)]), vec![
+        (Severity::Warning, "large_gap".to_string()),
+    ]);
+    // 1.1km in a minute, or without time
+    assert_eq!(rules(vec![test_timed_waypoint(42.0, 0), test_timed_waypoint(42.01, 60)]), vec![
+        (Severity::Error, "impossible_jump".to_string()),
+    ]);
+    assert_eq!(rules(vec![test_waypoint(42.0, 0.0, None), test_waypoint(42.01, 0.0, None)]), vec![
+        (Severity::Error, "impossible_jump".to_string()),
+    ]);
+}
+
+// END FUNCTIONS =======================================================================================  END FUNCTIONS
+
+//
+// * End of file lint_utils.rs
+//
@@ -0,0 +1,189 @@
+///
+/// # mbtiles_utils.rs
+/// This file contains the packing of tiles in a single MBTiles (SQLite) archive,
+/// instead of one `require` per tile in the app bundle, and the JSON index of what it contains.
+///
+/// ## Author
+/// * Tom Planche - <github.com/tomPlanche>
+
+// IMPORTS ===================================================================================================  IMPORTS
+use std::collections::BTreeMap;
+use std::fs::{read, remove_file};
+use std::path::Path;
+
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+
+use crate::tile_utils::{calc_tiles_bbox, TileFiles, TileKey};
+// END IMPORTS ==========================================================================================   END IMPORTS
+
+// VARIABLES ================================================================================================ VARIABLE
+// Schema of the MBTiles 1.3 specification
+const MBTILES_SCHEMA: &str = "
+    CREATE TABLE metadata (name TEXT, value TEXT);
+    CREATE TABLE tiles (zoom_level INTEGER, tile_column INTEGER, tile_row INTEGER, tile_data BLOB);
+    CREATE UNIQUE INDEX tile_index ON tiles (zoom_level, tile_column, tile_row);
+";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TilePackIndex {
+    pub archive: String,
+    pub name: String,
+    // Extension of the tiles: png, jpg or webp
+    pub format: String,
+    pub min_zoom: u8,
+    pub max_zoom: u8,
+    // [west, south, east, north], like the MBTiles metadata
+    pub bounds: [f64; 4],
+    pub tiles: usize,
+    pub tiles_per_zoom: BTreeMap<u8, usize>,
+    pub bytes: u64,
+    // Trails the tiles were selected for, empty when the whole folder is packed
+    pub trails: Vec<String>,
+}
+// END VARIABLES =======================================================================================  END VARIABLES
+
+// FUNCTIONS ================================================================================================ FUNCTIONS
+///
+/// # calc_pack_index
+/// Describe the tiles of a pack.
+///
+/// ## Arguments
+/// * `archive` - The file name of the archive.
+/// * `name` - The name of the pack.
+/// * `tiles` - The tiles packed, not empty.
+/// * `trails` - The trails the tiles were selected for.
+///
+/// ## Returns
+/// * `TilePackIndex` - The index of the pack.
+#[allow(dead_code)]
+pub fn calc_pack_index(archive: &str, name: &str, tiles: &TileFiles, trails: Vec<String>) -> TilePackIndex {
+    let keys: Vec<&TileKey> = tiles.keys().collect();
+
+    let format: String = tiles
+        .values()
+        .find_map(|file| file.path.extension()?.to_str().map(str::to_lowercase))
+        .map_or("png".to_string(), |extension| if extension == "jpeg" { "jpg".to_string() } else { extension });
+
+    // The bounds of the tiles of the highest zoom, the closest to the covered area
+    let max_zoom: u8 = keys.iter().map(|(zoom, _, _)| *zoom).max().unwrap_or(0);
+    let bounds: [f64; 4] = calc_tiles_bbox(keys.iter().copied()).unwrap_or([-180.0, -85.051_128_78, 180.0, 85.051_128_78]);
+
+    let mut tiles_per_zoom: BTreeMap<u8, usize> = BTreeMap::new();
+    for (zoom, _, _) in &keys {
+        *tiles_per_zoom.entry(*zoom).or_default() += 1;
+    }
+
+    TilePackIndex {
+        archive: archive.to_string(),
+        name: name.to_string(),
+        format,
+        min_zoom: keys.iter().map(|(zoom, _, _)| *zoom).min().unwrap_or(0),
+        max_zoom,
+        bounds,
+        tiles: tiles.len(),
+        tiles_per_zoom,
+        bytes: tiles.values().map(|file| file.bytes).sum(),
+        trails,
+    }
+}
+
+///
+/// # write_mbtiles
+/// Write tiles and their metadata in an MBTiles archive, replacing the file if it exists.
+/// The rows are flipped to the TMS scheme of the specification.
+///
+/// ## Arguments
+/// * `destination` - The path of the archive.
+/// * `tiles` - The tiles to pack.
+/// * `index` - The index of the pack, for the metadata.
+///
+/// ## Returns
+/// * `Result<(), String>` - An error message if a tile or the archive could not be written.
+#[allow(dead_code)]
+pub fn write_mbtiles(destination: &Path, tiles: &TileFiles, index: &TilePackIndex) -> Result<(), String> {
+    if destination.exists() {
+        remove_file(destination).map_err(|error| format!("Could not remove {:?}: {}", destination, error))?;
+    }
+
+    let mut connection = Connection::open(destination).map_err(|error| error.to_string())?;
+    connection.execute_batch(MBTILES_SCHEMA).map_err(|error| error.to_string())?;
+
+    let transaction = connection.transaction().map_err(|error| error.to_string())?;
+    {
+        let bounds: Vec<String> = index.bounds.iter().map(|bound| bound.to_string()).collect();
+        let metadata: [(&str, String); 8] = [
+            ("name", index.name.clone()),
+            ("format", index.format.clone()),
+            ("type", "baselayer".to_string()),
+            ("version", "1.0".to_string()),
+            ("minzoom", index.min_zoom.to_string()),
+            ("maxzoom", index.max_zoom.to_string()),
+            ("bounds", bounds.join(",")),
+            (
+                "description",
+                if index.trails.is_empty() {
+                    "Every tile of the folder".to_string()
+                } else {
+                    format!("Tiles covering: {}", index.trails.join(", "))
+                },
+            ),
+        ];
+
+        let mut insert_metadata = transaction
+            .prepare("INSERT INTO metadata (name, value) VALUES (?1, ?2)")
+            .map_err(|error| error.to_string())?;
+        for (name, value) in metadata {
+            insert_metadata.execute(params![name, value]).map_err(|error| error.to_string())?;
+        }
+
+        let mut insert_tile = transaction
+            .prepare("INSERT INTO tiles (zoom_level, tile_column, tile_row, tile_data) VALUES (?1, ?2, ?3, ?4)")
+            .map_err(|error| error.to_string())?;
+        for ((zoom, x, y), file) in tiles {
+            let data: Vec<u8> = read(&file.path).map_err(|error| format!("Could not read {:?}: {}", file.path, error))?;
+            let tms_row: u32 = (1u32 << zoom) - 1 - y;
+
+            insert_tile.execute(params![zoom, x, tms_row, data]).map_err(|error| error.to_string())?;
+        }
+    }
+
+    transaction.commit().map_err(|error| error.to_string())
+}
+
+#[test]
+fn test_write_mbtiles() {
+    use crate::tile_utils::TileFile;
+    use std::fs::{create_dir_all, remove_dir_all, write};
+
+    // The process id keeps parallel runs apart
+    let folder = std::env::temp_dir().join(format!("mbtiles_utils_test_{}", std::process::id()));
+    let _ = remove_dir_all(&folder);
+    create_dir_all(&folder).unwrap();
+    let tile_path = folder.join("6040.png");
+    write(&tile_path, [1u8, 2, 3]).unwrap();
+
+    let tiles: TileFiles = BTreeMap::from([((14, 8195, 6040), TileFile { path: tile_path, bytes: 3 })]);
+    let index = calc_pack_index("pack.mbtiles", "pack", &tiles, vec!["trail.gpx".to_string()]);
+    assert_eq!((index.format.as_str(), index.min_zoom, index.max_zoom, index.bytes), ("png", 14, 14, 3));
+
+    let destination = folder.join("pack.mbtiles");
+    write_mbtiles(&destination, &tiles, &index).unwrap();
+
+    let connection = Connection::open(&destination).unwrap();
+    let (row, data): (u32, Vec<u8>) = connection
+        .query_row("SELECT tile_row, tile_data FROM tiles WHERE zoom_level = 14 AND tile_column = 8195", [], |row| {
+            Ok((row.get(0)?, row.get(1)?))
+        })
+        .unwrap();
+    assert_eq!((row, data), ((1 << 14) - 1 - 6040, vec![1, 2, 3]));
+
+    drop(connection);
+    remove_dir_all(&folder).unwrap();
+}
+
+// END FUNCTIONS =======================================================================================  END FUNCTIONS
+
+//
+// * End of file mbtiles_utils.rs
+//
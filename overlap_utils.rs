@@ -0,0 +1,273 @@
+///
+/// # overlap_utils.rs
+/// This file contains the index of the common coordinates found by the `comparator`,
+/// queryable in both orientations of a pair of files, and the length of the overlaps.
+///
+/// ## Author
+/// * Tom Planche - <github.com/tomPlanche>
+
+// IMPORTS ===================================================================================================  IMPORTS
+use std::collections::{BTreeMap, BTreeSet};
+
+use serde::{Deserialize, Serialize};
+
+use crate::analysis_utils::calc_track_length;
+use crate::gpx_utils::{calc_distance, Coord, DistanceUnit};
+use crate::utils::FileCoordsHM;
+// END IMPORTS ==========================================================================================   END IMPORTS
+
+// VARIABLES ================================================================================================ VARIABLE
+// The common coordinates of every file with every other one, whatever the file they are stored under in 'final.json'.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct OverlapIndex {
+    // Map of each file to the other files to the common indexes, as (index in the file, index in the other file)
+    overlaps: BTreeMap<String, BTreeMap<String, Vec<(usize, usize)>>>,
+    // Map of each file to its common point indexes to the other files containing them
+    points: BTreeMap<String, BTreeMap<usize, BTreeSet<String>>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OverlapSummary {
+    pub file_1: String,
+    pub file_2: String,
+    pub common_points: usize,
+    // Length of file_1 shared with file_2 (in meters), and its percentage of file_1
+    pub length_1: f64,
+    pub percent_1: f64,
+    // Length of file_2 shared with file_1 (in meters), and its percentage of file_2
+    pub length_2: f64,
+    pub percent_2: f64,
+}
+// END VARIABLES =======================================================================================  END VARIABLES
+
+// FUNCTIONS ================================================================================================ FUNCTIONS
+impl OverlapIndex {
+    ///
+    /// # from_map
+    /// Index the common coordinates map of the `comparator`, in both orientations.
+    ///
+    /// ## Arguments
+    /// * `file_coords_map` - The map read from 'final.json'.
+    ///
+    /// ## Returns
+    /// * `OverlapIndex` - The index.
+    #[allow(dead_code)]
+    pub fn from_map(file_coords_map: &FileCoordsHM) -> OverlapIndex {
+        let mut index: OverlapIndex = OverlapIndex::default();
+
+        for (file_1, other_files) in file_coords_map {
+            for (file_2, common_indexes) in other_files {
+                index.add_pair(file_1, file_2, common_indexes.iter().copied());
+                index.add_pair(file_2, file_1, common_indexes.iter().map(|(index_1, index_2)| (*index_2, *index_1)));
+            }
+        }
+
+        // A pair stored under both files is only kept once
+        for other_files in index.overlaps.values_mut() {
+            for common_indexes in other_files.values_mut() {
+                common_indexes.sort_unstable();
+                common_indexes.dedup();
+            }
+        }
+
+        index
+    }
+
+    ///
+    /// # add_pair
+    /// Add the common indexes of a pair in one orientation.
+    fn add_pair(&mut self, file_1: &str, file_2: &str, common_indexes: impl Iterator<Item = (usize, usize)>) {
+        let pair_indexes = self.overlaps.entry(file_1.to_string()).or_default().entry(file_2.to_string()).or_default();
+        let points = self.points.entry(file_1.to_string()).or_default();
+
+        for (index_1, index_2) in common_indexes {
+            pair_indexes.push((index_1, index_2));
+            points.entry(index_1).or_default().insert(file_2.to_string());
+        }
+    }
+
+    ///
+    /// # overlaps
+    /// The common indexes of two files, in the orientation of the arguments.
+    ///
+    /// ## Arguments
+    /// * `file_1` - The first file.
+    /// * `file_2` - The second file.
+    ///
+    /// ## Returns
+    /// * `&[(usize, usize)]` - The sorted (index in file_1, index in file_2), empty if they do not overlap.
+    #[allow(dead_code)]
+    pub fn overlaps(&self, file_1: &str, file_2: &str) -> &[(usize, usize)] {
+        self.overlaps
+            .get(file_1)
+            .and_then(|other_files| other_files.get(file_2))
+            .map_or(&[], Vec::as_slice)
+    }
+
+    ///
+    /// # overlapping_files
+    /// The files sharing coordinates with a file.
+    ///
+    /// ## Arguments
+    /// * `file` - The file.
+    ///
+    /// ## Returns
+    /// * `Vec<(&str, usize)>` - The sorted names of the other files, with their number of common indexes.
+    #[allow(dead_code)]
+    pub fn overlapping_files(&self, file: &str) -> Vec<(&str, usize)> {
+        self.overlaps.get(file).map_or(Vec::new(), |other_files| {
+            other_files
+                .iter()
+                .filter(|(_, common_indexes)| !common_indexes.is_empty())
+                .map(|(other_file, common_indexes)| (other_file.as_str(), common_indexes.len()))
+                .collect()
+        })
+    }
+
+    ///
+    /// # files_containing
+    /// The other files passing by a point of a file.
+    ///
+    /// ## Arguments
+    /// * `file` - The file.
+    /// * `index` - The index of the point in the file.
+    ///
+    /// ## Returns
+    /// * `Vec<&str>` - The sorted names of the other files.
+    #[allow(dead_code)]
+    pub fn files_containing(&self, file: &str, index: usize) -> Vec<&str> {
+        self.points
+            .get(file)
+            .and_then(|points| points.get(&index))
+            .map_or(Vec::new(), |files| files.iter().map(String::as_str).collect())
+    }
+
+    ///
+    /// # pairs
+    /// The pairs of overlapping files, each once with the first file before the second one.
+    #[allow(dead_code)]
+    pub fn pairs(&self) -> Vec<(&str, &str)> {
+        self.overlaps
+            .iter()
+            .flat_map(|(file_1, other_files)| {
+                other_files
+                    .iter()
+                    .filter(move |(file_2, common_indexes)| file_1 < *file_2 && !common_indexes.is_empty())
+                    .map(move |(file_2, _)| (file_1.as_str(), file_2.as_str()))
+            })
+            .collect()
+    }
+
+    ///
+    /// # summarize
+    /// Measure the overlap of two files along each of them.
+    ///
+    /// ## Arguments
+    /// * `file_1` - The first file.
+    /// * `file_2` - The second file.
+    /// * `coords_1` - The coordinates of the first file.
+    /// * `coords_2` - The coordinates of the second file.
+    ///
+    /// ## Returns
+    /// * `OverlapSummary` - The number of common points, the shared length and percentage of each file.
+    #[allow(dead_code)]
+    pub fn summarize(&self, file_1: &str, file_2: &str, coords_1: &[Coord], coords_2: &[Coord]) -> OverlapSummary {
+        let common_indexes: &[(usize, usize)] = self.overlaps(file_1, file_2);
+        let indexes_1: BTreeSet<usize> = common_indexes.iter().map(|(index_1, _)| *index_1).collect();
+        let indexes_2: BTreeSet<usize> = common_indexes.iter().map(|(_, index_2)| *index_2).collect();
+
+        let length_1: f64 = calc_overlap_length(coords_1, &indexes_1);
+        let length_2: f64 = calc_overlap_length(coords_2, &indexes_2);
+        let percent = |length: f64, coords: &[Coord]| {
+            let track_length: f64 = calc_track_length(coords);
+
+            if track_length > 0.0 { length / track_length * 100.0 } else { 0.0 }
+        };
+
+        OverlapSummary {
+            file_1: file_1.to_string(),
+            file_2: file_2.to_string(),
+            common_points: common_indexes.len(),
+            length_1,
+            percent_1: percent(length_1, coords_1),
+            length_2,
+            percent_2: percent(length_2, coords_2),
+        }
+    }
+
+    ///
+    /// # files
+    /// The files sharing coordinates with at least one other file, sorted.
+    #[allow(dead_code)]
+    pub fn files(&self) -> Vec<&str> {
+        self.overlaps
+            .iter()
+            .filter(|(_, other_files)| other_files.values().any(|common_indexes| !common_indexes.is_empty()))
+            .map(|(file, _)| file.as_str())
+            .collect()
+    }
+}
+
+///
+/// # calc_overlap_length
+/// Calculate the length of the shared sections of a track: the segments between two consecutive common points.
+///
+/// ## Arguments
+/// * `coords` - The coordinates of the track.
+/// * `common_indexes` - The indexes of its common points.
+///
+/// ## Returns
+/// * `f64` - The shared length (in meters).
+#[allow(dead_code)]
+pub fn calc_overlap_length(coords: &[Coord], common_indexes: &BTreeSet<usize>) -> f64 {
+    common_indexes
+        .iter()
+        .filter(|index| **index + 1 < coords.len() && common_indexes.contains(&(**index + 1)))
+        .map(|index| calc_distance(coords[*index], coords[index + 1], DistanceUnit::Meters))
+        .sum()
+}
+
+#[test]
+fn test_overlap_index() {
+    use std::collections::HashMap;
+
+    let file_coords_map: FileCoordsHM = HashMap::from([
+        ("a.gpx".to_string(), HashMap::from([("b.gpx".to_string(), vec![(3, 10), (1, 12)])])),
+        ("c.gpx".to_string(), HashMap::from([("a.gpx".to_string(), vec![(7, 3)])])),
+    ]);
+    let index: OverlapIndex = OverlapIndex::from_map(&file_coords_map);
+
+    // Both orientations, whatever the file the pair is stored under
+    assert_eq!(index.overlaps("a.gpx", "b.gpx"), &[(1, 12), (3, 10)]);
+    assert_eq!(index.overlaps("b.gpx", "a.gpx"), &[(10, 3), (12, 1)]);
+    assert_eq!(index.overlaps("a.gpx", "c.gpx"), &[(3, 7)]);
+    assert!(index.overlaps("b.gpx", "c.gpx").is_empty());
+
+    assert_eq!(index.overlapping_files("a.gpx"), vec![("b.gpx", 2), ("c.gpx", 1)]);
+    assert_eq!(index.files_containing("a.gpx", 3), vec!["b.gpx", "c.gpx"]);
+    assert!(index.files_containing("a.gpx", 2).is_empty());
+    assert_eq!(index.files(), vec!["a.gpx", "b.gpx", "c.gpx"]);
+    assert_eq!(index.pairs(), vec![("a.gpx", "b.gpx"), ("a.gpx", "c.gpx")]);
+}
+
+#[test]
+fn test_summarize() {
+    use std::collections::HashMap;
+
+    // 4 points about 111m apart, the 2 first shared with the 2 last of the other track
+    let coords: Vec<Coord> = (0..4).map(|index| Coord { lat: 42.0 + index as f64 * 0.001, lon: 0.0 }).collect();
+    let file_coords_map: FileCoordsHM =
+        HashMap::from([("a.gpx".to_string(), HashMap::from([("b.gpx".to_string(), vec![(0, 2), (1, 3)])]))]);
+
+    let summary = OverlapIndex::from_map(&file_coords_map).summarize("b.gpx", "a.gpx", &coords, &coords);
+
+    assert_eq!(summary.common_points, 2);
+    assert!((summary.length_1 - 111.19).abs() < 0.1);
+    assert!((summary.percent_2 - 100.0 / 3.0).abs() < 1e-9);
+}
+
+// END FUNCTIONS =======================================================================================  END FUNCTIONS
+
+//
+// * End of file overlap_utils.rs
+//
@@ -0,0 +1,143 @@
+/// # project.rs
+/// This binary converts coordinates between `Coord` and a projection
+/// (`utm30n`, `utm31n`, `web_mercator`, `lambert93` or their EPSG code),
+/// and exports GPX files of the 'assets' folder as projected CSV for the GIS tools.
+///
+/// Arguments:
+/// * `<projection> <coord>` - A json representing a Coord, printed as projected meters.
+/// * `<projection> --inverse <x> <y>` - Projected meters, printed as a Coord.
+/// * `<projection> <file.gpx> [tolerance]` - Saves '<file>_<epsg>.csv' in the 'output' folder,
+///   simplified in the projected plane when a tolerance (in meters) is given.
+///
+/// ## Author
+/// * Tom Planche - <github.com/tomPlanche>
+///
+/// ## Example
+/// >> cargo run --bin project lambert93 "{\"lat\": 46.5, \"lon\": 3.0}"
+/// {"x":700000.0,"y":6600000.000000001}
+
+// IMPORTS ===================================================================================================  IMPORTS
+#[path = "gpx_utils.rs"]
+mod gpx_utils;
+
+#[path = "file_utils.rs"]
+mod file_utils;
+
+#[path = "utils.rs"]
+mod utils;
+
+#[path = "analysis_utils.rs"]
+mod analysis_utils;
+
+#[path = "elevation_utils.rs"]
+mod elevation_utils;
+
+#[path = "projection_utils.rs"]
+mod projection_utils;
+
+use std::fs::write;
+use std::path::PathBuf;
+
+use crate::analysis_utils::calc_track_length;
+use crate::file_utils::{file_name_to_path_buf, file_stem, get_output_file_path, read_gpx_points};
+use crate::gpx_utils::{Coord, Point};
+use crate::projection_utils::{
+    calc_planar_track_length,
+    project,
+    simplify_coords,
+    unproject,
+    ProjectedCoord,
+    Projection,
+};
+// END IMPORTS ==========================================================================================   END IMPORTS
+
+// FUNCTIONS ================================================================================================ FUNCTIONS
+///
+/// # export_gpx_file
+/// Save the projected points of a GPX file as CSV.
+///
+/// ## Arguments
+/// * `file_name` - The name of the GPX file in the 'assets' folder.
+/// * `projection` - The projection.
+/// * `tolerance` - The simplification tolerance (in meters), None to keep every point.
+fn export_gpx_file(file_name: &str, projection: Projection, tolerance: Option<f64>) {
+    let path_buff_from_file: PathBuf = file_name_to_path_buf(file_name);
+    let points: Vec<Point> = match read_gpx_points(&path_buff_from_file) {
+        Some(points) => points,
+        None => panic!("Could not read the file {:?}", file_name),
+    };
+
+    let coords: Vec<Coord> = points.iter().map(|point| point.coords).collect();
+    let kept: Vec<usize> = match tolerance {
+        Some(tolerance) => simplify_coords(&coords, tolerance, projection),
+        None => (0..points.len()).collect(),
+    };
+
+    println!(
+        "{} points kept of {}, length: {:.1}m on the sphere, {:.1}m in {}",
+        kept.len(),
+        points.len(),
+        calc_track_length(&coords),
+        calc_planar_track_length(&coords, projection),
+        projection
+    );
+
+    let mut csv: String = String::from("index,x,y,elevation\n");
+    for index in kept {
+        let projected: ProjectedCoord = project(points[index].coords, projection);
+        let elevation: String = points[index].elevation.map_or(String::new(), |elevation| elevation.to_string());
+
+        csv.push_str(&format!("{},{:.3},{:.3},{}\n", index, projected.x, projected.y, elevation));
+    }
+
+    let destination: PathBuf = get_output_file_path(&format!("{}_{}.csv", file_stem(file_name), projection.epsg()));
+
+    match write(&destination, csv) {
+        Ok(_) => println!("Successfully saved to: {}", destination.display()),
+        Err(error) => panic!("Could not write the file {:?}: {}", destination, error),
+    }
+}
+// END FUNCTIONS =======================================================================================  END FUNCTIONS
+
+// CODE ========================================================================================================= CODE
+fn main() {
+    let args: Vec<String> = std::env::args().collect();
+
+    if args.len() < 3 || args.len() > 5 {
+        panic!("This binary takes a projection, then a json Coord, '--inverse <x> <y>' or a GPX file name");
+    }
+
+    let projection: Projection = match args[1].parse() {
+        Ok(projection) => projection,
+        Err(message) => panic!("{}", message),
+    };
+
+    if args[2] == "--inverse" {
+        if args.len() != 5 {
+            panic!("The inverse conversion takes the x and y of the projected point");
+        }
+
+        let projected = ProjectedCoord {
+            x: args[3].parse().expect("The x must be a number"),
+            y: args[4].parse().expect("The y must be a number"),
+        };
+
+        println!("{}", serde_json::to_string(&unproject(projected, projection)).unwrap());
+    } else if args[2].ends_with(".gpx") {
+        let tolerance: Option<f64> = args.get(3).map(|tolerance| tolerance.parse().expect("The tolerance must be a number"));
+
+        export_gpx_file(&args[2], projection, tolerance);
+    } else {
+        let coord: Coord = match serde_json::from_str(&args[2]) {
+            Ok(coord) => coord,
+            Err(_) => panic!("Could not parse the second argument as a Coord"),
+        };
+
+        println!("{}", serde_json::to_string(&project(coord, projection)).unwrap());
+    }
+}
+// END CODE =======================================================================================  END COMPONENT
+
+//
+// * End of file /project.rs
+//
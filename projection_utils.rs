@@ -0,0 +1,443 @@
+///
+/// # projection_utils.rs
+/// This file contains the conversions between `Coord` and projected coordinates
+/// (UTM 30N/31N, Web Mercator, Lambert-93), and the geometry done in a projected plane:
+/// distances, track lengths and simplification.
+///
+/// ## Author
+/// * Tom Planche - <github.com/tomPlanche>
+
+// IMPORTS ===================================================================================================  IMPORTS
+use std::f64::consts::{FRAC_PI_2, FRAC_PI_4};
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use crate::gpx_utils::{Coord, WGS84_A, WGS84_F};
+// END IMPORTS ==========================================================================================   END IMPORTS
+
+// VARIABLES ================================================================================================ VARIABLE
+// Flattening of the GRS80 ellipsoid of Lambert-93, which shares the semi-major axis of WGS84.
+const GRS80_F: f64 = 1.0 / 298.257_222_101;
+
+// UTM scale factor on the central meridian and false easting.
+const UTM_K0: f64 = 0.9996;
+const UTM_FALSE_EASTING: f64 = 500_000.0;
+
+// Lambert-93: origin, standard parallels (in degrees) and false easting and northing.
+const LAMBERT_93_LAT_0: f64 = 46.5;
+const LAMBERT_93_LON_0: f64 = 3.0;
+const LAMBERT_93_LAT_1: f64 = 44.0;
+const LAMBERT_93_LAT_2: f64 = 49.0;
+const LAMBERT_93_X_0: f64 = 700_000.0;
+const LAMBERT_93_Y_0: f64 = 6_600_000.0;
+
+// Web Mercator stops at this latitude (in degrees), where the map becomes square.
+const WEB_MERCATOR_MAX_LAT: f64 = 85.051_128_779_806_59;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Projection {
+    Utm30N,
+    Utm31N,
+    WebMercator,
+    Lambert93,
+}
+
+// Easting and northing (in meters) in a projection
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ProjectedCoord {
+    pub x: f64,
+    pub y: f64,
+}
+// END VARIABLES =======================================================================================  END VARIABLES
+
+// FUNCTIONS ================================================================================================ FUNCTIONS
+impl Projection {
+    ///
+    /// # epsg
+    /// The EPSG code of the projection, for the GIS exports.
+    #[allow(dead_code)]
+    pub fn epsg(self) -> u32 {
+        match self {
+            Projection::Utm30N => 32630,
+            Projection::Utm31N => 32631,
+            Projection::WebMercator => 3857,
+            Projection::Lambert93 => 2154,
+        }
+    }
+}
+
+impl Display for Projection {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Projection::Utm30N => write!(f, "utm30n"),
+            Projection::Utm31N => write!(f, "utm31n"),
+            Projection::WebMercator => write!(f, "web_mercator"),
+            Projection::Lambert93 => write!(f, "lambert93"),
+        }
+    }
+}
+
+impl FromStr for Projection {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "utm30n" | "32630" => Ok(Projection::Utm30N),
+            "utm31n" | "32631" => Ok(Projection::Utm31N),
+            "web_mercator" | "3857" => Ok(Projection::WebMercator),
+            "lambert93" | "2154" => Ok(Projection::Lambert93),
+            other => Err(format!(
+                "Unknown projection {:?}, expected 'utm30n', 'utm31n', 'web_mercator', 'lambert93' or their EPSG code",
+                other
+            )),
+        }
+    }
+}
+
+///
+/// # utm_series
+/// The rectifying radius and the coefficients of the Krüger series (to n^4, sub-millimetre in a zone).
+///
+/// ## Returns
+/// * `(f64, [f64; 4], [f64; 4], [f64; 4])` - The radius, the forward (alpha), inverse (beta) and latitude (delta) coefficients.
+fn utm_series() -> (f64, [f64; 4], [f64; 4], [f64; 4]) {
+    let n: f64 = WGS84_F / (2.0 - WGS84_F);
+    let (n2, n3, n4) = (n * n, n * n * n, n * n * n * n);
+
+    let radius: f64 = WGS84_A / (1.0 + n) * (1.0 + n2 / 4.0 + n4 / 64.0);
+
+    let alpha = [
+        n / 2.0 - 2.0 * n2 / 3.0 + 5.0 * n3 / 16.0 + 41.0 * n4 / 180.0,
+        13.0 * n2 / 48.0 - 3.0 * n3 / 5.0 + 557.0 * n4 / 1440.0,
+        61.0 * n3 / 240.0 - 103.0 * n4 / 140.0,
+        49561.0 * n4 / 161280.0,
+    ];
+    let beta = [
+        n / 2.0 - 2.0 * n2 / 3.0 + 37.0 * n3 / 96.0 - n4 / 360.0,
+        n2 / 48.0 + n3 / 15.0 - 437.0 * n4 / 1440.0,
+        17.0 * n3 / 480.0 - 37.0 * n4 / 840.0,
+        4397.0 * n4 / 161280.0,
+    ];
+    let delta = [
+        2.0 * n - 2.0 * n2 / 3.0 - 2.0 * n3 + 116.0 * n4 / 45.0,
+        7.0 * n2 / 3.0 - 8.0 * n3 / 5.0 - 227.0 * n4 / 45.0,
+        56.0 * n3 / 15.0 - 136.0 * n4 / 35.0,
+        4279.0 * n4 / 630.0,
+    ];
+
+    (radius, alpha, beta, delta)
+}
+
+///
+/// # utm_forward
+/// Project a coordinate in a northern UTM zone.
+fn utm_forward(coord: Coord, lon_0: f64) -> ProjectedCoord {
+    let (radius, alpha, _, _) = utm_series();
+    let n: f64 = WGS84_F / (2.0 - WGS84_F);
+    let e: f64 = 2.0 * n.sqrt() / (1.0 + n);
+
+    let lat: f64 = coord.lat.to_radians();
+    let delta_lon: f64 = (coord.lon - lon_0).to_radians();
+
+    // Conformal latitude
+    let t: f64 = (lat.sin().atanh() - e * (e * lat.sin()).atanh()).sinh();
+    let xi_prime: f64 = t.atan2(delta_lon.cos());
+    let eta_prime: f64 = (delta_lon.sin() / (1.0 + t * t).sqrt()).atanh();
+
+    let (mut xi, mut eta) = (xi_prime, eta_prime);
+    for (j, alpha_j) in alpha.iter().enumerate() {
+        let k: f64 = 2.0 * (j + 1) as f64;
+        xi += alpha_j * (k * xi_prime).sin() * (k * eta_prime).cosh();
+        eta += alpha_j * (k * xi_prime).cos() * (k * eta_prime).sinh();
+    }
+
+    ProjectedCoord {
+        x: UTM_FALSE_EASTING + UTM_K0 * radius * eta,
+        y: UTM_K0 * radius * xi,
+    }
+}
+
+///
+/// # utm_inverse
+/// Get the coordinate of a point of a northern UTM zone.
+fn utm_inverse(projected: ProjectedCoord, lon_0: f64) -> Coord {
+    let (radius, _, beta, delta) = utm_series();
+
+    let xi: f64 = projected.y / (UTM_K0 * radius);
+    let eta: f64 = (projected.x - UTM_FALSE_EASTING) / (UTM_K0 * radius);
+
+    let (mut xi_prime, mut eta_prime) = (xi, eta);
+    for (j, beta_j) in beta.iter().enumerate() {
+        let k: f64 = 2.0 * (j + 1) as f64;
+        xi_prime -= beta_j * (k * xi).sin() * (k * eta).cosh();
+        eta_prime -= beta_j * (k * xi).cos() * (k * eta).sinh();
+    }
+
+    let chi: f64 = (xi_prime.sin() / eta_prime.cosh()).asin();
+    let lat: f64 = chi
+        + delta
+            .iter()
+            .enumerate()
+            .map(|(j, delta_j)| delta_j * (2.0 * (j + 1) as f64 * chi).sin())
+            .sum::<f64>();
+
+    Coord {
+        lat: lat.to_degrees(),
+        lon: lon_0 + eta_prime.sinh().atan2(xi_prime.cos()).to_degrees(),
+    }
+}
+
+///
+/// # isometric_latitude
+/// The isometric latitude of a latitude (in radians) on an ellipsoid of eccentricity `e`.
+fn isometric_latitude(lat: f64, e: f64) -> f64 {
+    (FRAC_PI_4 + lat / 2.0).tan().ln() - e / 2.0 * ((1.0 + e * lat.sin()) / (1.0 - e * lat.sin())).ln()
+}
+
+///
+/// # lambert_93_constants
+/// The eccentricity, cone constant, scale and northing of the pole of Lambert-93.
+fn lambert_93_constants() -> (f64, f64, f64, f64) {
+    let e: f64 = (GRS80_F * (2.0 - GRS80_F)).sqrt();
+    let (lat_1, lat_2) = (LAMBERT_93_LAT_1.to_radians(), LAMBERT_93_LAT_2.to_radians());
+
+    // Radius of curvature in the prime vertical times the cosine, at a latitude
+    let m = |lat: f64| lat.cos() / (1.0 - e * e * lat.sin() * lat.sin()).sqrt();
+
+    let cone: f64 = (m(lat_1) / m(lat_2)).ln() / (isometric_latitude(lat_2, e) - isometric_latitude(lat_1, e));
+    let scale: f64 = WGS84_A * m(lat_1) / cone * (cone * isometric_latitude(lat_1, e)).exp();
+    let pole_y: f64 = LAMBERT_93_Y_0 + scale * (-cone * isometric_latitude(LAMBERT_93_LAT_0.to_radians(), e)).exp();
+
+    (e, cone, scale, pole_y)
+}
+
+///
+/// # project
+/// Convert a coordinate to a projection.
+///
+/// ## Arguments
+/// * `coord` - The coordinate in decimal degrees.
+/// * `projection` - The projection.
+///
+/// ## Returns
+/// * `ProjectedCoord` - The projected coordinate (in meters).
+#[allow(dead_code)]
+pub fn project(coord: Coord, projection: Projection) -> ProjectedCoord {
+    match projection {
+        Projection::Utm30N => utm_forward(coord, -3.0),
+        Projection::Utm31N => utm_forward(coord, 3.0),
+        Projection::WebMercator => {
+            let lat: f64 = coord.lat.clamp(-WEB_MERCATOR_MAX_LAT, WEB_MERCATOR_MAX_LAT).to_radians();
+
+            ProjectedCoord {
+                x: WGS84_A * coord.lon.to_radians(),
+                y: WGS84_A * (FRAC_PI_4 + lat / 2.0).tan().ln(),
+            }
+        }
+        Projection::Lambert93 => {
+            let (e, cone, scale, pole_y) = lambert_93_constants();
+
+            let radius: f64 = scale * (-cone * isometric_latitude(coord.lat.to_radians(), e)).exp();
+            let gamma: f64 = cone * (coord.lon - LAMBERT_93_LON_0).to_radians();
+
+            ProjectedCoord {
+                x: LAMBERT_93_X_0 + radius * gamma.sin(),
+                y: pole_y - radius * gamma.cos(),
+            }
+        }
+    }
+}
+
+///
+/// # unproject
+/// Convert a projected coordinate back to a coordinate.
+///
+/// ## Arguments
+/// * `projected` - The projected coordinate (in meters).
+/// * `projection` - The projection.
+///
+/// ## Returns
+/// * `Coord` - The coordinate in decimal degrees.
+#[allow(dead_code)]
+pub fn unproject(projected: ProjectedCoord, projection: Projection) -> Coord {
+    match projection {
+        Projection::Utm30N => utm_inverse(projected, -3.0),
+        Projection::Utm31N => utm_inverse(projected, 3.0),
+        Projection::WebMercator => Coord {
+            lat: (2.0 * (projected.y / WGS84_A).exp().atan() - FRAC_PI_2).to_degrees(),
+            lon: (projected.x / WGS84_A).to_degrees(),
+        },
+        Projection::Lambert93 => {
+            let (e, cone, scale, pole_y) = lambert_93_constants();
+
+            let dx: f64 = projected.x - LAMBERT_93_X_0;
+            let dy: f64 = pole_y - projected.y;
+            let isometric: f64 = -(dx.hypot(dy) / scale).ln() / cone;
+
+            // Fixed point on the latitude, converging in a few iterations
+            let mut lat: f64 = 2.0 * isometric.exp().atan() - FRAC_PI_2;
+            for _ in 0..20 {
+                let next: f64 = 2.0
+                    * (((1.0 + e * lat.sin()) / (1.0 - e * lat.sin())).powf(e / 2.0) * isometric.exp()).atan()
+                    - FRAC_PI_2;
+
+                let converged: bool = (next - lat).abs() < 1e-12;
+                lat = next;
+                if converged {
+                    break;
+                }
+            }
+
+            Coord {
+                lat: lat.to_degrees(),
+                lon: LAMBERT_93_LON_0 + (dx.atan2(dy) / cone).to_degrees(),
+            }
+        }
+    }
+}
+
+///
+/// # calc_planar_distance
+/// Calculate the distance between two coordinates in a projected plane.
+/// In Web Mercator the distances are stretched by 1/cos(lat), about 1.36 in the Pyrenees.
+///
+/// ## Arguments
+/// * `coord1` - The first coordinate in decimal degrees.
+/// * `coord2` - The second coordinate in decimal degrees.
+/// * `projection` - The projection.
+///
+/// ## Returns
+/// * `f64` - The distance (in projected meters).
+#[allow(dead_code)]
+pub fn calc_planar_distance(coord1: Coord, coord2: Coord, projection: Projection) -> f64 {
+    let (p1, p2) = (project(coord1, projection), project(coord2, projection));
+
+    (p2.x - p1.x).hypot(p2.y - p1.y)
+}
+
+///
+/// # calc_planar_track_length
+/// Calculate the length of a track in a projected plane.
+///
+/// ## Arguments
+/// * `coords` - The coordinates of the track.
+/// * `projection` - The projection.
+///
+/// ## Returns
+/// * `f64` - The length (in projected meters).
+#[allow(dead_code)]
+pub fn calc_planar_track_length(coords: &[Coord], projection: Projection) -> f64 {
+    let projected: Vec<ProjectedCoord> = coords.iter().map(|coord| project(*coord, projection)).collect();
+
+    projected.windows(2).map(|pair| (pair[1].x - pair[0].x).hypot(pair[1].y - pair[0].y)).sum()
+}
+
+///
+/// # point_segment_distance
+/// Distance from a point to a segment in the plane.
+fn point_segment_distance(point: ProjectedCoord, start: ProjectedCoord, end: ProjectedCoord) -> f64 {
+    let (dx, dy) = (end.x - start.x, end.y - start.y);
+    let length_sq: f64 = dx * dx + dy * dy;
+
+    let t: f64 = if length_sq == 0.0 {
+        0.0
+    } else {
+        (((point.x - start.x) * dx + (point.y - start.y) * dy) / length_sq).clamp(0.0, 1.0)
+    };
+
+    (point.x - start.x - t * dx).hypot(point.y - start.y - t * dy)
+}
+
+///
+/// # simplify_coords
+/// Simplify a track with the Douglas-Peucker algorithm in a projected plane.
+///
+/// ## Arguments
+/// * `coords` - The coordinates of the track.
+/// * `tolerance` - The maximum distance (in projected meters) between the track and its simplification.
+/// * `projection` - The projection.
+///
+/// ## Returns
+/// * `Vec<usize>` - The indexes of the kept coordinates, the first and last always kept.
+#[allow(dead_code)]
+pub fn simplify_coords(coords: &[Coord], tolerance: f64, projection: Projection) -> Vec<usize> {
+    if coords.len() < 3 {
+        return (0..coords.len()).collect();
+    }
+
+    let projected: Vec<ProjectedCoord> = coords.iter().map(|coord| project(*coord, projection)).collect();
+    let mut kept: Vec<bool> = vec![false; coords.len()];
+    kept[0] = true;
+    kept[coords.len() - 1] = true;
+
+    // Iterative, the tracks have thousands of points
+    let mut stack: Vec<(usize, usize)> = vec![(0, coords.len() - 1)];
+    while let Some((start, end)) = stack.pop() {
+        let farthest = (start + 1..end)
+            .map(|index| (index, point_segment_distance(projected[index], projected[start], projected[end])))
+            .max_by(|a, b| a.1.total_cmp(&b.1));
+
+        if let Some((index, distance)) = farthest {
+            if distance > tolerance {
+                kept[index] = true;
+                stack.push((start, index));
+                stack.push((index, end));
+            }
+        }
+    }
+
+    (0..coords.len()).filter(|index| kept[*index]).collect()
+}
+
+#[test]
+fn test_projections_reference_values() {
+    // UTM: 3 degrees from the central meridian on the equator, and k0 times the meridian arc at 45 degrees
+    let edge = project(Coord { lat: 0.0, lon: 6.0 }, Projection::Utm31N);
+    assert!((edge.x - 833_978.556).abs() < 0.001 && edge.y.abs() < 0.001);
+
+    let meridian = project(Coord { lat: 45.0, lon: -3.0 }, Projection::Utm30N);
+    assert!((meridian.x - 500_000.0).abs() < 0.001 && (meridian.y - 4_982_950.400).abs() < 0.001);
+
+    // Lambert-93: the origin of the projection
+    let origin = project(Coord { lat: 46.5, lon: 3.0 }, Projection::Lambert93);
+    assert!((origin.x - 700_000.0).abs() < 0.001 && (origin.y - 6_600_000.0).abs() < 0.001);
+
+    // Web Mercator: the edge of the square map
+    let corner = project(Coord { lat: WEB_MERCATOR_MAX_LAT, lon: 180.0 }, Projection::WebMercator);
+    assert!((corner.x - 20_037_508.343).abs() < 0.001 && (corner.y - 20_037_508.343).abs() < 0.001);
+}
+
+#[test]
+fn test_projections_round_trip() {
+    let coord = Coord { lat: 42.6782, lon: 0.0856 };
+
+    for projection in [Projection::Utm30N, Projection::Utm31N, Projection::WebMercator, Projection::Lambert93] {
+        let back = unproject(project(coord, projection), projection);
+
+        assert!((back.lat - coord.lat).abs() < 1e-9, "{}", projection);
+        assert!((back.lon - coord.lon).abs() < 1e-9, "{}", projection);
+    }
+}
+
+#[test]
+fn test_simplify_coords() {
+    // A straight line with a small bump, then a corner
+    let coords = vec![
+        Coord { lat: 42.0, lon: 0.0 },
+        Coord { lat: 42.001, lon: 0.00001 },
+        Coord { lat: 42.002, lon: 0.0 },
+        Coord { lat: 42.002, lon: 0.002 },
+    ];
+
+    assert_eq!(simplify_coords(&coords, 5.0, Projection::Utm31N), vec![0, 2, 3]);
+    assert_eq!(simplify_coords(&coords, 0.1, Projection::Utm31N), vec![0, 1, 2, 3]);
+}
+
+// END FUNCTIONS =======================================================================================  END FUNCTIONS
+
+//
+// * End of file projection_utils.rs
+//
@@ -0,0 +1,119 @@
+/// # query.rs
+/// This binary finds the trails of the 'assets' folder intersecting a bounding box
+/// or passing within a radius of a Coord, with the index ranges of their points and segments matching.
+/// The bounding box of each track is cached in the 'bbox_index.json' file of the 'output' folder,
+/// and the result saved in its 'query.json' file.
+///
+/// Arguments:
+/// * `bbox <min_coord> <max_coord>` - Two jsons representing the south-west and north-east corners.
+/// * `near <coord> <radius>` - A json representing a Coord and a radius (in meters).
+///
+/// ## Author
+/// * Tom Planche - <github.com/tomPlanche>
+///
+/// ## Example
+/// >> cargo run --bin query near "{\"lat\": 42.6782, \"lon\": 0.0856}" 500
+/// Balcon2-1.gpx: [(0, 16), (479, 500)]
+/// EmbalseCuezoPradera.gpx: [(1790, 1826)]
+/// puertoviejofenars.gpx: [(0, 22), (522, 549)]
+/// Successfully saved to: output/query.json
+
+// IMPORTS ===================================================================================================  IMPORTS
+#[path = "gpx_utils.rs"]
+mod gpx_utils;
+
+#[path = "file_utils.rs"]
+mod file_utils;
+
+#[path = "utils.rs"]
+mod utils;
+
+#[path = "bbox_utils.rs"]
+mod bbox_utils;
+
+use std::path::PathBuf;
+
+use crate::bbox_utils::{find_index_ranges, load_bbox_index, BboxIndex, BoundingBox, RegionQuery, TrailMatch};
+use crate::file_utils::{get_output_file_path, look_4_files, read_file_name, read_gpx_file, save_serializable_to_json};
+use crate::gpx_utils::Coord;
+// END IMPORTS ==========================================================================================   END IMPORTS
+
+// FUNCTIONS ================================================================================================ FUNCTIONS
+///
+/// # parse_coord
+/// Parse an argument as a Coord.
+fn parse_coord(arg: &str, position: &str) -> Coord {
+    match serde_json::from_str(arg) {
+        Ok(coord) => coord,
+        Err(_) => panic!("Could not parse the {} argument as a Coord", position),
+    }
+}
+// END FUNCTIONS =======================================================================================  END FUNCTIONS
+
+// CODE ========================================================================================================= CODE
+fn main() {
+    let args: Vec<String> = std::env::args().collect();
+
+    if args.len() != 4 {
+        panic!("This binary takes 'bbox <min_coord> <max_coord>' or 'near <coord> <radius>'");
+    }
+
+    let query: RegionQuery = match args[1].as_str() {
+        "bbox" => {
+            let min: Coord = parse_coord(&args[2], "first");
+            let max: Coord = parse_coord(&args[3], "second");
+
+            RegionQuery::Bbox(BoundingBox {
+                min_lat: min.lat.min(max.lat),
+                min_lon: min.lon.min(max.lon),
+                max_lat: min.lat.max(max.lat),
+                max_lon: min.lon.max(max.lon),
+            })
+        }
+        "near" => RegionQuery::Near {
+            coord: parse_coord(&args[2], "first"),
+            radius: args[3].parse().expect("The radius must be a number"),
+        },
+        other => panic!("Unknown query {:?}, expected 'bbox' or 'near'", other),
+    };
+
+    let gpx_files: Vec<PathBuf> = look_4_files();
+    let index: BboxIndex = load_bbox_index(&gpx_files);
+    let query_bbox: BoundingBox = query.bbox();
+
+    let mut matches: Vec<TrailMatch> = Vec::new();
+
+    for file in &gpx_files {
+        let file_name: String = match read_file_name(file) {
+            Some(file_name) => file_name,
+            None => panic!("Could not read the file name of {:?}", file),
+        };
+
+        // Skip the tracks whose box is far from the query without reading them
+        if !index.get(&file_name).is_some_and(|entry| entry.bbox.intersects(&query_bbox)) {
+            continue;
+        }
+
+        let coords: Vec<Coord> = match read_gpx_file(file) {
+            Some(coords) => coords,
+            None => panic!("Could not read the file {:?}", file),
+        };
+
+        let ranges: Vec<(usize, usize)> = find_index_ranges(&coords, &query);
+        if !ranges.is_empty() {
+            println!("{}: {:?}", file_name, ranges);
+            matches.push(TrailMatch { file: file_name, ranges });
+        }
+    }
+
+    if matches.is_empty() {
+        println!("No trail found");
+    }
+
+    save_serializable_to_json(&matches, &get_output_file_path("query.json"));
+}
+// END CODE =======================================================================================  END COMPONENT
+
+//
+// * End of file /query.rs
+//
@@ -1,24 +1,20 @@
 /// # reader.rs
-/// This module will compare the GPX files in the 'gpx_files' folder and will output a JSON file
-/// with a map of the common coordinates between the files.
-///
-/// The final map will look like this:
-/// ```json
-/// {
-///   "file_1": {
-///     "file_2": [(index_file_1, index_file_2), ...],
-///     "file_3": [(index_file_1, index_file_3), ...],
-///     ...
-///   },
-///   "file_2": {
-///     ...
-///   },
-///   ...
-/// }
-/// ```
+/// This binary reads the common coordinates found by the `comparator` in the 'final.json' file
+/// and answers questions about them, as a table or as json with the `--json` argument.
+///
+/// Arguments:
+/// * `pairs <file_1> <file_2>` - The common indexes of two files, as (index in file_1, index in file_2).
+/// * `coords <file_1> <file_2>` - The common coordinates of two files.
+/// * `summary` - For each pair of overlapping files, the shared length (in meters) and percentage of each file.
+/// * `neighbors <file>` - The files sharing path with a file, the longest overlap first.
 ///
 /// ## Author
 /// * Tom Planche - <github.com/tomPlanche>
+///
+/// ## Example
+/// >> cargo run --bin reader neighbors puertoviejofenars.gpx
+/// trail                    common points  shared length (m)  % of puertoviejofenars.gpx  % of trail
+/// EmbalseCuezoPradera.gpx  81             1403.8             11.6                        5.4
 
 // IMPORTS ===================================================================================================  IMPORTS
 #[path = "gpx_utils.rs"]
@@ -30,14 +26,26 @@ mod file_utils;
 #[path = "utils.rs"]
 mod utils;
 
+#[path = "elevation_utils.rs"]
+mod elevation_utils;
+
+#[path = "analysis_utils.rs"]
+mod analysis_utils;
+
+#[path = "overlap_utils.rs"]
+mod overlap_utils;
+
+use std::collections::BTreeMap;
 use std::path::PathBuf;
 use file_utils::get_final_json_path;
-use ansi_term::Colour::{Green, Red};
+use ansi_term::Colour::{Green, Red, Yellow};
+use ansi_term::Style;
+use serde::Serialize;
 
 
-use crate::file_utils::{load_from_json, file_name_to_path_buf, read_gpx_file};
+use crate::file_utils::{load_from_json, file_name_to_path_buf, read_gpx_file, read_gpx_files};
 use crate::gpx_utils::Coord;
-use crate::utils::{FileCoordsHM};
+use crate::overlap_utils::{OverlapIndex, OverlapSummary};
 // END IMPORTS ==========================================================================================   END IMPORTS
 
 
@@ -54,51 +62,21 @@ fn check_if_file_exists() -> bool {
 
 ///
 /// # read_from_file
-/// Read from the output file and returns the hashmap.
+/// Read the output file once and index it.
 ///
 /// ## Returns
-/// * `FileCoordsHM` - The hashmap read from the file
-fn read_from_file() -> FileCoordsHM {
+/// * `OverlapIndex` - The index of the common coordinates, empty without output file
+fn read_from_file() -> OverlapIndex {
     if !check_if_file_exists() {
         println!("{}", Red.paint("The output file does not exist"));
         println!("{}", Green.paint("Run the comparator first"));
 
-        return FileCoordsHM::new();
+        return OverlapIndex::default();
     }
 
     let path_buff: PathBuf = get_final_json_path();
 
-    load_from_json(&path_buff)
-}
-
-///
-/// # find_common_coords
-/// Find the common coordinates between the two files.
-///
-/// ## Arguments
-/// * `file_1` - The first file
-/// * `file_2` - The second file
-///
-/// ## Returns
-/// * `Vec<(usize, usize)>` - The vector of common coordinates
-fn find_common_coords_indexes(
-    file_1: &str,
-    file_2: &str
-) -> Vec<(usize, usize)> {
-    let mut common_coords: Vec<(usize, usize)> = Vec::new();
-    let file_coords_map: FileCoordsHM = read_from_file();
-
-    let (final_file_1, final_file_2): (String, String) = if file_coords_map.contains_key(file_2) {
-        (file_2.to_string(), file_1.to_string())
-    } else {
-        (file_1.to_string(), file_2.to_string())
-    };
-
-    if file_coords_map.contains_key(&final_file_1) && file_coords_map[&final_file_1].contains_key(&final_file_2) {
-        common_coords = file_coords_map[&final_file_1][&final_file_2].clone();
-    }
-
-    common_coords
+    OverlapIndex::from_map(&load_from_json(&path_buff))
 }
 
 ///
@@ -111,7 +89,7 @@ fn find_common_coords_indexes(
 ///
 /// ## Returns
 /// * `Vec<(Coord, Coord)>` - The vector of coordinates
-fn indexes_to_coords(file_1: &str, file_2: &str, indexes: &Vec<(usize, usize)>) -> Vec<(Coord, Coord)> {
+fn indexes_to_coords(file_1: &str, file_2: &str, indexes: &[(usize, usize)]) -> Vec<(Coord, Coord)> {
     let path_buff_from_file_1: PathBuf = file_name_to_path_buf(file_1);
     let path_buff_from_file_2: PathBuf = file_name_to_path_buf(file_2);
 
@@ -137,13 +115,220 @@ fn indexes_to_coords(file_1: &str, file_2: &str, indexes: &Vec<(usize, usize)>)
     coords_pairs
 }
 
-fn main() {
-    println!("{:?}", find_common_coords_indexes("puertoviejofenars.gpx", "EmbalseCuezoPradera.gpx"));
-    print!("{:?}", indexes_to_coords("puertoviejofenars.gpx", "EmbalseCuezoPradera.gpx", &find_common_coords_indexes("puertoviejofenars.gpx", "EmbalseCuezoPradera.gpx")));
+///
+/// # read_coords
+/// Read the coordinates of files of the 'assets' folder, in parallel.
+/// The files removed since the 'final.json' file was written are skipped with a warning.
+///
+/// ## Arguments
+/// * `file_names` - The names of the files
+///
+/// ## Returns
+/// * `BTreeMap<String, Vec<Coord>>` - The coordinates of each file found, by file name
+fn read_coords(file_names: &[&str]) -> BTreeMap<String, Vec<Coord>> {
+    let (paths, missing): (Vec<PathBuf>, Vec<PathBuf>) = file_names
+        .iter()
+        .map(|file_name| file_name_to_path_buf(file_name))
+        .partition(|path| path.exists());
+
+    if !missing.is_empty() {
+        // On stderr, to keep the output valid json with '--json'
+        eprintln!(
+            "{}",
+            Yellow.paint(format!(
+                "{} file(s) of the 'final.json' file not found in 'assets', skipped: {:?}. Run 'comparator --incremental' to update it.",
+                missing.len(),
+                missing.iter().filter_map(|path| path.file_name()).collect::<Vec<_>>(),
+            ))
+        );
+    }
+
+    read_gpx_files(&paths)
+}
+
+///
+/// # print_json
+/// Print a value as json.
+fn print_json<T: Serialize>(value: &T) {
+    match serde_json::to_string_pretty(value) {
+        Ok(json) => println!("{}", json),
+        Err(error) => panic!("Could not serialize the result: {}", error),
+    }
 }
 
+///
+/// # print_table
+/// Print rows as aligned columns, under a bold header.
+///
+/// ## Arguments
+/// * `headers` - The title of each column
+/// * `rows` - The cells of each row
+fn print_table(headers: &[&str], rows: &[Vec<String>]) {
+    let widths: Vec<usize> = headers
+        .iter()
+        .enumerate()
+        .map(|(column, header)| {
+            rows.iter()
+                .map(|row| row[column].chars().count())
+                .fold(header.chars().count(), usize::max)
+        })
+        .collect();
+
+    // The padding is added before painting, the color codes having no width
+    let format_row = |cells: Vec<&str>| {
+        cells
+            .iter()
+            .zip(&widths)
+            .map(|(cell, width)| format!("{:<width$}", cell, width = width))
+            .collect::<Vec<String>>()
+            .join("  ")
+            .trim_end()
+            .to_string()
+    };
+
+    println!("{}", Style::new().bold().paint(format_row(headers.to_vec())));
+    for row in rows {
+        println!("{}", format_row(row.iter().map(String::as_str).collect()));
+    }
+}
 // END FUNCTIONS =======================================================================================  END FUNCTIONS
 
+// CODE ========================================================================================================= CODE
+fn main() {
+    let mut args: Vec<String> = std::env::args().collect();
+
+    let json: bool = args.iter().any(|arg| arg == "--json");
+    args.retain(|arg| arg != "--json");
+
+    let usage: &str = "This binary takes 'pairs <file_1> <file_2>', 'coords <file_1> <file_2>', 'summary' or 'neighbors <file>', \
+        and optionally '--json'";
+
+    let overlap_index: OverlapIndex = read_from_file();
+
+    match (args.get(1).map(String::as_str), args.len()) {
+        (Some("pairs"), 4) => {
+            let common_indexes: &[(usize, usize)] = overlap_index.overlaps(&args[2], &args[3]);
+
+            if json {
+                print_json(&common_indexes);
+            } else if common_indexes.is_empty() {
+                println!("No common coordinates between {} and {}", args[2], args[3]);
+            } else {
+                let rows: Vec<Vec<String>> = common_indexes
+                    .iter()
+                    .map(|(index_1, index_2)| vec![index_1.to_string(), index_2.to_string()])
+                    .collect();
+
+                print_table(&[&args[2], &args[3]], &rows);
+            }
+        }
+        (Some("coords"), 4) => {
+            let common_coords: Vec<(Coord, Coord)> =
+                indexes_to_coords(&args[2], &args[3], overlap_index.overlaps(&args[2], &args[3]));
+
+            if json {
+                print_json(&common_coords);
+            } else if common_coords.is_empty() {
+                println!("No common coordinates between {} and {}", args[2], args[3]);
+            } else {
+                let rows: Vec<Vec<String>> = common_coords
+                    .iter()
+                    .map(|(coord_1, coord_2)| {
+                        vec![
+                            format!("{:.6}, {:.6}", coord_1.lat, coord_1.lon),
+                            format!("{:.6}, {:.6}", coord_2.lat, coord_2.lon),
+                        ]
+                    })
+                    .collect();
+
+                print_table(&[&args[2], &args[3]], &rows);
+            }
+        }
+        (Some("summary"), 2) => {
+            let gpx_coords_map: BTreeMap<String, Vec<Coord>> = read_coords(&overlap_index.files());
+
+            let summaries: Vec<OverlapSummary> = overlap_index
+                .pairs()
+                .into_iter()
+                .filter_map(|(file_1, file_2)| {
+                    Some(overlap_index.summarize(file_1, file_2, gpx_coords_map.get(file_1)?, gpx_coords_map.get(file_2)?))
+                })
+                .collect();
+
+            if json {
+                print_json(&summaries);
+            } else if summaries.is_empty() {
+                println!("No overlapping files");
+            } else {
+                let rows: Vec<Vec<String>> = summaries
+                    .iter()
+                    .map(|summary| {
+                        vec![
+                            summary.file_1.clone(),
+                            summary.file_2.clone(),
+                            summary.common_points.to_string(),
+                            format!("{:.1}", summary.length_1),
+                            format!("{:.1}", summary.percent_1),
+                            format!("{:.1}", summary.length_2),
+                            format!("{:.1}", summary.percent_2),
+                        ]
+                    })
+                    .collect();
+
+                print_table(
+                    &["file 1", "file 2", "common points", "length 1 (m)", "% of file 1", "length 2 (m)", "% of file 2"],
+                    &rows,
+                );
+            }
+        }
+        (Some("neighbors"), 3) => {
+            let file: &str = &args[2];
+            let mut file_names: Vec<&str> = vec![file];
+            file_names.extend(overlap_index.overlapping_files(file).into_iter().map(|(other_file, _)| other_file));
+
+            let gpx_coords_map: BTreeMap<String, Vec<Coord>> = if file_names.len() > 1 {
+                read_coords(&file_names)
+            } else {
+                BTreeMap::new()
+            };
+
+            let mut summaries: Vec<OverlapSummary> = file_names[1..]
+                .iter()
+                .filter_map(|other_file| {
+                    Some(overlap_index.summarize(file, other_file, gpx_coords_map.get(file)?, gpx_coords_map.get(*other_file)?))
+                })
+                .collect();
+            summaries.sort_by(|summary_1, summary_2| summary_2.length_1.total_cmp(&summary_1.length_1));
+
+            if json {
+                print_json(&summaries);
+            } else if summaries.is_empty() {
+                println!("No trail sharing path with {}", file);
+            } else {
+                let rows: Vec<Vec<String>> = summaries
+                    .iter()
+                    .map(|summary| {
+                        vec![
+                            summary.file_2.clone(),
+                            summary.common_points.to_string(),
+                            format!("{:.1}", summary.length_1),
+                            format!("{:.1}", summary.percent_1),
+                            format!("{:.1}", summary.percent_2),
+                        ]
+                    })
+                    .collect();
+
+                print_table(
+                    &["trail", "common points", "shared length (m)", &format!("% of {}", file), "% of trail"],
+                    &rows,
+                );
+            }
+        }
+        _ => panic!("{}", usage),
+    }
+}
+// END CODE =======================================================================================  END COMPONENT
+
 //
-// * End of file file_utils.rs
+// * End of file /reader.rs
 //
@@ -0,0 +1,127 @@
+/// # route.rs
+/// This binary finds a route across the trail network between two Coord.
+/// It uses the 'graph.json' file of the `trail_graph` binary.
+///
+/// Arguments:
+/// * start and end - Two jsons representing two Coord.
+/// * mode (optional) - `distance` (default) or `effort` to take the elevation into account.
+/// * format (optional) - `geojson` (default) or `gpx`.
+///
+/// ## Author
+/// * Tom Planche - <github.com/tomPlanche>
+///
+/// ## Example
+/// >> cargo run --bin route "{\"lat\": 42.6782, \"lon\": 0.0856}" "{\"lat\": 42.6897, \"lon\": 0.2072}" effort gpx
+/// Trails used: ["EspadaIbonetCircuito.gpx", "puertoviejofenars.gpx"]
+/// Successfully saved to: output/route.gpx
+
+// IMPORTS ===================================================================================================  IMPORTS
+#[path = "gpx_utils.rs"]
+mod gpx_utils;
+
+#[path = "file_utils.rs"]
+mod file_utils;
+
+#[path = "utils.rs"]
+mod utils;
+
+#[path = "elevation_utils.rs"]
+mod elevation_utils;
+
+#[path = "graph_utils.rs"]
+mod graph_utils;
+
+#[path = "route_utils.rs"]
+mod route_utils;
+
+use std::path::PathBuf;
+
+use ansi_term::Colour::{Green, Red};
+
+use crate::file_utils::{
+    get_output_file_path,
+    load_serializable_from_json,
+    save_serializable_to_json,
+    write_gpx_file,
+};
+use crate::gpx_utils::{Coord, Point};
+use crate::graph_utils::TrailGraph;
+use crate::route_utils::{find_route, route_to_geojson, CostMode, Route};
+// END IMPORTS ==========================================================================================   END IMPORTS
+
+// CODE ========================================================================================================= CODE
+fn main() {
+    let args: Vec<String> = std::env::args().collect();
+
+    if args.len() < 3 || args.len() > 5 {
+        panic!("This binary takes two jsons representing two Coord, then optionally a mode and a format");
+    }
+
+    let start: Coord = match serde_json::from_str(&args[1]) {
+        Ok(coord) => coord,
+        Err(_) => panic!("Could not parse the first argument as a Coord"),
+    };
+
+    let end: Coord = match serde_json::from_str(&args[2]) {
+        Ok(coord) => coord,
+        Err(_) => panic!("Could not parse the second argument as a Coord"),
+    };
+
+    let mode: CostMode = match args.get(3).map(String::as_str) {
+        None | Some("distance") => CostMode::Distance,
+        Some("effort") => CostMode::Effort,
+        Some(other) => panic!("Unknown mode {:?}, expected 'distance' or 'effort'", other),
+    };
+
+    let graph_path: PathBuf = get_output_file_path("graph.json");
+    if !graph_path.exists() {
+        println!("{}", Red.paint("The graph file does not exist"));
+        println!("{}", Green.paint("Run the trail_graph first"));
+
+        return;
+    }
+
+    let graph: TrailGraph = load_serializable_from_json(&graph_path);
+
+    let route: Route = match find_route(&graph, start, end, mode) {
+        Some(route) => route,
+        None => {
+            println!("{}", Red.paint("No route found between the two points"));
+            return;
+        }
+    };
+
+    println!("Trails used: {:?}", route.trails);
+    println!("Length: {:.0}m, ascent: {:.0}m, descent: {:.0}m", route.length, route.ascent, route.descent);
+
+    match args.get(4).map(String::as_str) {
+        None | Some("geojson") => {
+            save_serializable_to_json(&route_to_geojson(&route), &get_output_file_path("route.geojson"));
+        }
+        Some("gpx") => {
+            let points: Vec<Point> = route
+                .coords
+                .iter()
+                .map(|coord| Point {
+                    coords: *coord,
+                    name: None,
+                    description: None,
+                    elevation: None,
+                })
+                .collect();
+
+            write_gpx_file(
+                &points,
+                "route",
+                Some(format!("Trails used: {}", route.trails.join(", "))),
+                &get_output_file_path("route.gpx"),
+            );
+        }
+        Some(other) => panic!("Unknown format {:?}, expected 'geojson' or 'gpx'", other),
+    }
+}
+// END CODE =======================================================================================  END COMPONENT
+
+//
+// * End of file /route.rs
+//
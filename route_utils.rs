@@ -0,0 +1,358 @@
+///
+/// # route_utils.rs
+/// This file contains the functions used to find a route across the trail network graph.
+///
+/// The start and end coordinates are snapped on the closest edge point,
+/// then the shortest path is found with Dijkstra's algorithm.
+///
+/// ## Author
+/// * Tom Planche - <github.com/tomPlanche>
+
+// IMPORTS ===================================================================================================  IMPORTS
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::gpx_utils::{calc_distance, Coord, DistanceUnit};
+use crate::graph_utils::{GraphEdge, TrailGraph};
+// END IMPORTS ==========================================================================================   END IMPORTS
+
+// VARIABLES ================================================================================================ VARIABLE
+// Naismith's rule: climbing 100m takes as long as walking 1km on the flat.
+#[allow(dead_code)]
+pub const EFFORT_METERS_PER_ASCENT_METER: f64 = 10.0;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[allow(dead_code)]
+pub enum CostMode {
+    Distance,
+    Effort,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Snap {
+    pub edge: usize,
+    pub index: usize,
+    pub distance: f64,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Route {
+    pub coords: Vec<Coord>,
+    pub length: f64,
+    pub ascent: f64,
+    pub descent: f64,
+    pub trails: Vec<String>,
+    pub edges: Vec<usize>,
+}
+
+// Part of an edge walked by the route: points `start..=end` of `edge`, backwards if `start > end`.
+#[derive(Debug, Clone, Copy)]
+struct Leg {
+    edge: usize,
+    start: usize,
+    end: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct State {
+    cost: f64,
+    node: usize,
+}
+
+impl Eq for State {}
+
+// Reversed ordering so the BinaryHeap pops the lowest cost first.
+impl Ord for State {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.cost.partial_cmp(&self.cost).unwrap_or(Ordering::Equal)
+    }
+}
+
+impl PartialOrd for State {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+// END VARIABLES =======================================================================================  END VARIABLES
+
+// FUNCTIONS ================================================================================================ FUNCTIONS
+///
+/// # snap_to_graph
+/// Find the edge point closest to a coordinate.
+///
+/// ## Arguments
+/// * `graph` - The trail network graph.
+/// * `coord` - The coordinate to snap.
+///
+/// ## Returns
+/// * `Option<Snap>` - The closest edge point, None if the graph has no edges.
+#[allow(dead_code)]
+pub fn snap_to_graph(graph: &TrailGraph, coord: Coord) -> Option<Snap> {
+    graph
+        .edges
+        .iter()
+        .flat_map(|edge| {
+            edge.coords.iter().enumerate().map(move |(index, edge_coord)| Snap {
+                edge: edge.id,
+                index,
+                distance: calc_distance(coord, *edge_coord, DistanceUnit::Meters),
+            })
+        })
+        .min_by(|snap_1, snap_2| snap_1.distance.partial_cmp(&snap_2.distance).unwrap())
+}
+
+///
+/// # leg_length
+/// Calculate the length of a leg.
+fn leg_length(edge: &GraphEdge, leg: &Leg) -> f64 {
+    let (low, high) = (leg.start.min(leg.end), leg.start.max(leg.end));
+
+    edge.coords[low..=high]
+        .windows(2)
+        .map(|pair| calc_distance(pair[0], pair[1], DistanceUnit::Meters))
+        .sum()
+}
+
+///
+/// # leg_ascent_descent
+/// Calculate the ascent and descent of a leg in its walking direction.
+/// Only the totals of the edge are known, so they are split proportionally to the length.
+fn leg_ascent_descent(edge: &GraphEdge, leg: &Leg) -> (f64, f64) {
+    let ratio = if edge.length > 0.0 { leg_length(edge, leg) / edge.length } else { 0.0 };
+
+    if leg.start <= leg.end {
+        (edge.ascent * ratio, edge.descent * ratio)
+    } else {
+        (edge.descent * ratio, edge.ascent * ratio)
+    }
+}
+
+///
+/// # leg_cost
+/// Calculate the cost of a leg for the given mode.
+fn leg_cost(edge: &GraphEdge, leg: &Leg, mode: CostMode) -> f64 {
+    let length = leg_length(edge, leg);
+
+    match mode {
+        CostMode::Distance => length,
+        CostMode::Effort => length + leg_ascent_descent(edge, leg).0 * EFFORT_METERS_PER_ASCENT_METER,
+    }
+}
+
+///
+/// # find_route
+/// Find the cheapest route between two coordinates across the trail network.
+///
+/// ## Arguments
+/// * `graph` - The trail network graph.
+/// * `start` - The start coordinate.
+/// * `end` - The end coordinate.
+/// * `mode` - What the route should minimize.
+///
+/// ## Returns
+/// * `Option<Route>` - The route, None if the two points are not connected.
+#[allow(dead_code)]
+pub fn find_route(graph: &TrailGraph, start: Coord, end: Coord, mode: CostMode) -> Option<Route> {
+    let start_snap = snap_to_graph(graph, start)?;
+    let end_snap = snap_to_graph(graph, end)?;
+
+    // The graph nodes plus two virtual nodes for the snapped start and end
+    let start_node = graph.nodes.len();
+    let end_node = start_node + 1;
+    let mut adjacency: Vec<Vec<(usize, Leg)>> = vec![Vec::new(); graph.nodes.len() + 2];
+
+    for edge in &graph.edges {
+        let last = edge.coords.len() - 1;
+
+        adjacency[edge.from].push((edge.to, Leg { edge: edge.id, start: 0, end: last }));
+        adjacency[edge.to].push((edge.from, Leg { edge: edge.id, start: last, end: 0 }));
+    }
+
+    for (node, snap) in [(start_node, start_snap), (end_node, end_snap)] {
+        let edge = &graph.edges[snap.edge];
+        let last = edge.coords.len() - 1;
+
+        let to_from = Leg { edge: edge.id, start: snap.index, end: 0 };
+        let to_to = Leg { edge: edge.id, start: snap.index, end: last };
+
+        if node == start_node {
+            adjacency[node].push((edge.from, to_from));
+            adjacency[node].push((edge.to, to_to));
+        } else {
+            adjacency[edge.from].push((node, Leg { edge: edge.id, start: 0, end: snap.index }));
+            adjacency[edge.to].push((node, Leg { edge: edge.id, start: last, end: snap.index }));
+        }
+    }
+
+    // Both points on the same edge, they can be joined without leaving it
+    if start_snap.edge == end_snap.edge {
+        adjacency[start_node].push((
+            end_node,
+            Leg { edge: start_snap.edge, start: start_snap.index, end: end_snap.index },
+        ));
+    }
+
+    // Dijkstra
+    let mut costs: Vec<f64> = vec![f64::INFINITY; adjacency.len()];
+    let mut previous: Vec<Option<(usize, Leg)>> = vec![None; adjacency.len()];
+    let mut heap: BinaryHeap<State> = BinaryHeap::new();
+
+    costs[start_node] = 0.0;
+    heap.push(State { cost: 0.0, node: start_node });
+
+    while let Some(State { cost, node }) = heap.pop() {
+        if node == end_node {
+            break;
+        }
+        if cost > costs[node] {
+            continue;
+        }
+
+        for (next, leg) in &adjacency[node] {
+            let next_cost = cost + leg_cost(&graph.edges[leg.edge], leg, mode);
+
+            if next_cost < costs[*next] {
+                costs[*next] = next_cost;
+                previous[*next] = Some((node, *leg));
+                heap.push(State { cost: next_cost, node: *next });
+            }
+        }
+    }
+
+    if costs[end_node].is_infinite() {
+        return None;
+    }
+
+    // Walk back from the end to get the legs in order
+    let mut legs: Vec<Leg> = Vec::new();
+    let mut node = end_node;
+    while let Some((previous_node, leg)) = previous[node] {
+        legs.push(leg);
+        node = previous_node;
+    }
+    legs.reverse();
+
+    let mut route = Route::default();
+    // Snapping on a junction gives legs of a single point, they are not part of the route
+    for leg in legs.iter().filter(|leg| leg.start != leg.end) {
+        let edge = &graph.edges[leg.edge];
+        let (ascent, descent) = leg_ascent_descent(edge, leg);
+
+        let mut coords: Vec<Coord> = edge.coords[leg.start.min(leg.end)..=leg.start.max(leg.end)].to_vec();
+        if leg.start > leg.end {
+            coords.reverse();
+        }
+
+        // The first point of a leg is the last point of the previous one
+        let skip = usize::from(!route.coords.is_empty());
+        route.coords.extend(coords.into_iter().skip(skip));
+
+        route.length += leg_length(edge, leg);
+        route.ascent += ascent;
+        route.descent += descent;
+
+        if route.edges.last() != Some(&edge.id) {
+            route.edges.push(edge.id);
+        }
+        for trail in &edge.trails {
+            if !route.trails.contains(trail) {
+                route.trails.push(trail.clone());
+            }
+        }
+    }
+
+    Some(route)
+}
+
+///
+/// # route_to_geojson
+/// Convert a route to a GeoJSON Feature.
+///
+/// ## Arguments
+/// * `route` - The route to convert.
+///
+/// ## Returns
+/// * `Value` - The GeoJSON `LineString` Feature.
+#[allow(dead_code)]
+pub fn route_to_geojson(route: &Route) -> Value {
+    json!({
+        "type": "Feature",
+        "geometry": {
+            "type": "LineString",
+            "coordinates": route
+                .coords
+                .iter()
+                .map(|coord| [coord.lon, coord.lat])
+                .collect::<Vec<[f64; 2]>>(),
+        },
+        "properties": {
+            "length": route.length,
+            "ascent": route.ascent,
+            "descent": route.descent,
+            "trails": route.trails,
+            "edges": route.edges,
+        },
+    })
+}
+
+#[cfg(test)]
+fn test_edge(id: usize, from: usize, to: usize, coords: Vec<Coord>, ascent: f64) -> GraphEdge {
+    use crate::graph_utils::EdgeSource;
+
+    let length: f64 = coords.windows(2).map(|pair| calc_distance(pair[0], pair[1], DistanceUnit::Meters)).sum();
+
+    GraphEdge {
+        id,
+        from,
+        to,
+        length,
+        ascent,
+        descent: 0.0,
+        trails: vec![format!("trail_{}.gpx", id)],
+        source: EdgeSource { trail: format!("trail_{}.gpx", id), start: 0, end: coords.len() - 1 },
+        coords,
+    }
+}
+
+#[test]
+fn test_find_route() {
+    use crate::graph_utils::GraphNode;
+
+    let a = Coord { lat: 42.0, lon: 0.0 };
+    let b = Coord { lat: 42.0, lon: 0.01 };
+    let top = Coord { lat: 42.001, lon: 0.005 };
+    let detour = Coord { lat: 42.01, lon: 0.005 };
+
+    // A short but steep edge and a long flat one between the same two nodes
+    let graph = TrailGraph {
+        nodes: vec![
+            GraphNode { id: 0, coords: a, degree: 2 },
+            GraphNode { id: 1, coords: b, degree: 2 },
+        ],
+        edges: vec![
+            test_edge(0, 0, 1, vec![a, top, b], 500.0),
+            test_edge(1, 0, 1, vec![a, detour, b], 0.0),
+        ],
+    };
+
+    let by_distance = find_route(&graph, a, b, CostMode::Distance).unwrap();
+    assert_eq!(by_distance.trails, vec!["trail_0.gpx".to_string()]);
+    assert_eq!(by_distance.coords, vec![a, top, b]);
+
+    let by_effort = find_route(&graph, a, b, CostMode::Effort).unwrap();
+    assert_eq!(by_effort.trails, vec!["trail_1.gpx".to_string()]);
+
+    // Backwards, the steep edge is a descent
+    let backwards = find_route(&graph, b, a, CostMode::Effort).unwrap();
+    assert_eq!(backwards.coords, vec![b, top, a]);
+    assert_eq!(backwards.descent, 500.0);
+}
+
+// END FUNCTIONS =======================================================================================  END FUNCTIONS
+
+//
+// * End of file route_utils.rs
+//
@@ -0,0 +1,115 @@
+/// # slope.rs
+/// This binary computes the grade of each segment of the GPX files, the distance
+/// in each difficulty band and the steepest sustained sections,
+/// and saves them in the 'slope.json' file of the 'output' folder.
+///
+/// Arguments:
+/// * --geojson (optional) - Also save a '<file>_slope.geojson' per file, with the grade of each line.
+/// * files (optional) - The names of GPX files in the 'assets' folder, all of them by default.
+///
+/// The final map will look like this:
+/// ```json
+/// {
+///   "file_1": {
+///     "bands": [{ "label": "<10%", "distance": 5210.3 }, ...],
+///     "max_grade": 41.2,
+///     "steepest_sections": [{ "min_length": 100.0, "start": 120, "end": 126, "length": 104.2, "grade": 31.5 }, ...]
+///   },
+///   ...
+/// }
+/// ```
+///
+/// ## Author
+/// * Tom Planche - <github.com/tomPlanche>
+///
+/// ## Example
+/// >> cargo run --bin slope --geojson EspadaIbonetCircuito.gpx
+/// EspadaIbonetCircuito.gpx: max grade 87%
+/// Successfully saved to: output/EspadaIbonetCircuito_slope.geojson
+/// Successfully saved to: output/slope.json
+
+// IMPORTS ===================================================================================================  IMPORTS
+#[path = "gpx_utils.rs"]
+mod gpx_utils;
+
+#[path = "file_utils.rs"]
+mod file_utils;
+
+#[path = "utils.rs"]
+mod utils;
+
+#[path = "elevation_utils.rs"]
+mod elevation_utils;
+
+#[path = "grade_utils.rs"]
+mod grade_utils;
+
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use ansi_term::Colour::Yellow;
+
+use crate::file_utils::{
+    file_name_to_path_buf,
+    file_stem,
+    get_output_file_path,
+    look_4_files,
+    read_file_name,
+    read_gpx_points,
+    save_serializable_to_json,
+};
+use crate::gpx_utils::Point;
+use crate::grade_utils::{calc_grade_report, calc_grades, grades_to_geojson, GradeReport};
+// END IMPORTS ==========================================================================================   END IMPORTS
+
+// CODE ========================================================================================================= CODE
+fn main() {
+    let mut args: Vec<String> = std::env::args().skip(1).collect();
+
+    let with_geojson: bool = args.iter().any(|arg| arg == "--geojson");
+    args.retain(|arg| arg != "--geojson");
+
+    let mut gpx_files: Vec<PathBuf> = args.iter().map(|file_name| file_name_to_path_buf(file_name)).collect();
+    if gpx_files.is_empty() {
+        gpx_files = look_4_files();
+    }
+
+    // Map of file names to their grade report, sorted by name
+    let mut reports: BTreeMap<String, GradeReport> = BTreeMap::new();
+
+    for file in &gpx_files {
+        let file_name: String = match read_file_name(file) {
+            Some(file_name) => file_name,
+            None => panic!("Could not read the file name of {:?}", file),
+        };
+
+        let points: Vec<Point> = match read_gpx_points(file) {
+            Some(points) => points,
+            None => panic!("Could not read the file {:?}", file),
+        };
+
+        if points.iter().all(|point| point.elevation.is_none()) {
+            println!("{}: {}", file_name, Yellow.paint("no elevation"));
+            continue;
+        }
+
+        let report: GradeReport = calc_grade_report(&points);
+        println!("{}: max grade {:.0}%", file_name, report.max_grade);
+
+        if with_geojson {
+            save_serializable_to_json(
+                &grades_to_geojson(&points, &calc_grades(&points)),
+                &get_output_file_path(&format!("{}_slope.geojson", file_stem(&file_name))),
+            );
+        }
+
+        reports.insert(file_name, report);
+    }
+
+    save_serializable_to_json(&reports, &get_output_file_path("slope.json"));
+}
+// END CODE =======================================================================================  END COMPONENT
+
+//
+// * End of file /slope.rs
+//
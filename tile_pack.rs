@@ -0,0 +1,159 @@
+/// # tile_pack.rs
+/// This binary packs a `z/x/y` tiles folder, or only the tiles covering trails,
+/// in a single MBTiles archive instead of one `require` per tile in the app bundle.
+/// The archive and its JSON index are saved in the 'output' folder.
+///
+/// Arguments:
+/// * tiles_folder - The tiles folder.
+/// * name - The name of the pack, used for '<name>.mbtiles' and '<name>_index.json'.
+/// * --trails (optional) - Only pack the tiles covering trails, followed by an optional buffer
+///   (in meters, 200 by default) and GPX file names (all of them by default).
+///
+/// ## Author
+/// * Tom Planche - <github.com/tomPlanche>
+///
+/// ## Example
+/// >> cargo run --bin tile_pack ../app/assets/tiles valpinera --trails 100 Balcon2-1.gpx
+/// 3 tiles missing from the folder, run tile_report for the list
+/// 3 tiles packed (0.1 MB)
+/// Successfully saved to: output/valpinera.mbtiles
+/// Successfully saved to: output/valpinera_index.json
+
+// IMPORTS ===================================================================================================  IMPORTS
+#[path = "gpx_utils.rs"]
+mod gpx_utils;
+
+#[path = "file_utils.rs"]
+mod file_utils;
+
+#[path = "utils.rs"]
+mod utils;
+
+#[path = "projection_utils.rs"]
+mod projection_utils;
+
+#[path = "tile_utils.rs"]
+mod tile_utils;
+
+#[path = "mbtiles_utils.rs"]
+mod mbtiles_utils;
+
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+
+use ansi_term::Colour::{Red, Yellow};
+
+use crate::file_utils::{
+    file_name_to_path_buf,
+    get_output_file_path,
+    look_4_files,
+    read_file_name,
+    read_gpx_file,
+    save_serializable_to_json,
+};
+use crate::gpx_utils::Coord;
+use crate::mbtiles_utils::{calc_pack_index, write_mbtiles, TilePackIndex};
+use crate::tile_utils::{calc_track_tiles, coverage_tiles, scan_tile_folder, TileFiles, TileKey};
+// END IMPORTS ==========================================================================================   END IMPORTS
+
+// VARIABLES ================================================================================================ VARIABLE
+const DEFAULT_BUFFER: f64 = 200.0;
+// END VARIABLES =======================================================================================  END VARIABLES
+
+// CODE ========================================================================================================= CODE
+fn main() {
+    let args: Vec<String> = std::env::args().collect();
+
+    if args.len() < 3 {
+        panic!("This binary takes a tiles folder and a pack name, then optionally '--trails [buffer] [files]'");
+    }
+
+    let tiles_folder: &Path = Path::new(&args[1]);
+    if !tiles_folder.exists() {
+        panic!("The folder {:?} does not exist", tiles_folder);
+    }
+    let name: &str = &args[2];
+
+    let (mut tiles, issues): (TileFiles, Vec<String>) = scan_tile_folder(tiles_folder);
+    if !issues.is_empty() {
+        println!("{}", Yellow.paint(format!("{} entries of the folder skipped, run tiles_to_json for the list", issues.len())));
+    }
+    if tiles.is_empty() {
+        println!("{}", Red.paint("The tiles folder does not contain any z/x/y tile"));
+        return;
+    }
+
+    let mut trails: Vec<String> = Vec::new();
+
+    match args.get(3).map(String::as_str) {
+        None => {}
+        Some("--trails") => {
+            let mut rest: &[String] = &args[4..];
+            let buffer: f64 = match rest.first() {
+                Some(buffer) if !buffer.ends_with(".gpx") => {
+                    rest = &rest[1..];
+                    buffer.parse().expect("The buffer must be a number")
+                }
+                _ => DEFAULT_BUFFER,
+            };
+
+            let mut gpx_files: Vec<PathBuf> = rest.iter().map(|file_name| file_name_to_path_buf(file_name)).collect();
+            if gpx_files.is_empty() {
+                gpx_files = look_4_files();
+            }
+
+            let zooms = tiles.keys().next().unwrap().0..=tiles.keys().next_back().unwrap().0;
+            let mut required: BTreeSet<TileKey> = BTreeSet::new();
+
+            for file in &gpx_files {
+                let file_name: String = match read_file_name(file) {
+                    Some(file_name) => file_name,
+                    None => panic!("Could not read the file name of {:?}", file),
+                };
+
+                let coords: Vec<Coord> = match read_gpx_file(file) {
+                    Some(coords) => coords,
+                    None => panic!("Could not read the file {:?}", file),
+                };
+
+                required.extend(coverage_tiles(&calc_track_tiles(&coords, zooms.clone(), buffer)));
+                trails.push(file_name);
+            }
+
+            // Only the zooms of the folder, which may skip some
+            let folder_zooms: BTreeSet<u8> = tiles.keys().map(|(zoom, _, _)| *zoom).collect();
+            let missing: usize = required
+                .iter()
+                .filter(|tile| folder_zooms.contains(&tile.0) && !tiles.contains_key(tile))
+                .count();
+            if missing > 0 {
+                println!("{}", Yellow.paint(format!("{} tiles missing from the folder, run tile_report for the list", missing)));
+            }
+
+            tiles.retain(|tile, _| required.contains(tile));
+        }
+        Some(other) => panic!("Unknown option {:?}, expected '--trails'", other),
+    }
+
+    if tiles.is_empty() {
+        println!("{}", Red.paint("No tile of the folder covers the trails"));
+        return;
+    }
+
+    let archive: String = format!("{}.mbtiles", name);
+    let index: TilePackIndex = calc_pack_index(&archive, name, &tiles, trails);
+    println!("{} tiles packed ({:.1} MB)", index.tiles, index.bytes as f64 / 1_000_000.0);
+
+    let destination: PathBuf = get_output_file_path(&archive);
+    match write_mbtiles(&destination, &tiles, &index) {
+        Ok(_) => println!("Successfully saved to: {}", destination.display()),
+        Err(message) => panic!("Could not write the archive: {}", message),
+    }
+
+    save_serializable_to_json(&index, &get_output_file_path(&format!("{}_index.json", name)));
+}
+// END CODE =======================================================================================  END COMPONENT
+
+//
+// * End of file /tile_pack.rs
+//
@@ -0,0 +1,144 @@
+/// # tile_report.rs
+/// This binary cross-checks a `z/x/y` tiles folder (the one `tiles_to_json` walks)
+/// against the tiles covering each GPX file, on the zooms of the folder,
+/// and saves the report in the 'tile_report.json' file of the 'output' folder:
+/// the missing tiles and the storage size per trail, and the tiles used by no trail.
+///
+/// Arguments:
+/// * tiles_folder - The tiles folder.
+/// * buffer (optional) - The buffer around the tracks (in meters), 200 by default.
+/// * files (optional) - The names of GPX files in the 'assets' folder, all of them by default.
+///
+/// ## Author
+/// * Tom Planche - <github.com/tomPlanche>
+///
+/// ## Example
+/// >> cargo run --bin tile_report ../app/assets/tiles
+/// Balcon2-1.gpx: 3 missing of 6 tiles (0.1 MB)
+/// ...
+/// 1 unused tiles (0.0 MB)
+/// Successfully saved to: output/tile_report.json
+
+// IMPORTS ===================================================================================================  IMPORTS
+#[path = "gpx_utils.rs"]
+mod gpx_utils;
+
+#[path = "file_utils.rs"]
+mod file_utils;
+
+#[path = "utils.rs"]
+mod utils;
+
+#[path = "projection_utils.rs"]
+mod projection_utils;
+
+#[path = "tile_utils.rs"]
+mod tile_utils;
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use ansi_term::Colour::{Green, Red, Yellow};
+
+use crate::file_utils::{
+    file_name_to_path_buf,
+    get_output_file_path,
+    look_4_files,
+    read_file_name,
+    read_gpx_file,
+    save_serializable_to_json,
+};
+use crate::gpx_utils::Coord;
+use crate::tile_utils::{build_tile_report, calc_track_tiles, scan_tile_folder, TileCoverage, TileFiles, TileReport};
+// END IMPORTS ==========================================================================================   END IMPORTS
+
+// VARIABLES ================================================================================================ VARIABLE
+const DEFAULT_BUFFER: f64 = 200.0;
+// END VARIABLES =======================================================================================  END VARIABLES
+
+// CODE ========================================================================================================= CODE
+fn main() {
+    let args: Vec<String> = std::env::args().collect();
+
+    if args.len() < 2 {
+        panic!("This binary takes a tiles folder, then optionally a buffer and GPX file names");
+    }
+
+    let tiles_folder: &Path = Path::new(&args[1]);
+    if !tiles_folder.exists() {
+        panic!("The folder {:?} does not exist", tiles_folder);
+    }
+
+    let mut rest: &[String] = &args[2..];
+    let buffer: f64 = match rest.first() {
+        Some(buffer) if !buffer.ends_with(".gpx") => {
+            rest = &rest[1..];
+            buffer.parse().expect("The buffer must be a number")
+        }
+        _ => DEFAULT_BUFFER,
+    };
+
+    let mut gpx_files: Vec<PathBuf> = rest.iter().map(|file_name| file_name_to_path_buf(file_name)).collect();
+    if gpx_files.is_empty() {
+        gpx_files = look_4_files();
+    }
+
+    let (files, issues): (TileFiles, Vec<String>) = scan_tile_folder(tiles_folder);
+    if !issues.is_empty() {
+        println!("{}", Yellow.paint(format!("{} entries of the folder skipped, run tiles_to_json for the list", issues.len())));
+    }
+
+    let (Some(min_zoom), Some(max_zoom)) = (files.keys().next(), files.keys().next_back()) else {
+        println!("{}", Red.paint("The tiles folder does not contain any z/x/y tile"));
+        return;
+    };
+    let zooms = min_zoom.0..=max_zoom.0;
+
+    let mut coverages: BTreeMap<String, TileCoverage> = BTreeMap::new();
+    for file in &gpx_files {
+        let file_name: String = match read_file_name(file) {
+            Some(file_name) => file_name,
+            None => panic!("Could not read the file name of {:?}", file),
+        };
+
+        let coords: Vec<Coord> = match read_gpx_file(file) {
+            Some(coords) => coords,
+            None => panic!("Could not read the file {:?}", file),
+        };
+
+        let mut coverage: TileCoverage = calc_track_tiles(&coords, zooms.clone(), buffer);
+        // Only the zooms of the folder, which may skip some
+        coverage.retain(|zoom, _| files.keys().any(|(file_zoom, _, _)| file_zoom == zoom));
+
+        coverages.insert(file_name, coverage);
+    }
+
+    let report: TileReport = build_tile_report(&files, &coverages, buffer);
+
+    for (trail, trail_report) in &report.trails {
+        let line = format!(
+            "{}: {} missing of {} tiles ({:.1} MB)",
+            trail,
+            trail_report.missing.len(),
+            trail_report.required,
+            trail_report.bytes as f64 / 1_000_000.0
+        );
+
+        if trail_report.missing.is_empty() {
+            println!("{}", Green.paint(line));
+        } else {
+            println!("{}", Red.paint(line));
+        }
+    }
+    println!(
+        "{}",
+        Yellow.paint(format!("{} unused tiles ({:.1} MB)", report.unused.len(), report.unused_bytes as f64 / 1_000_000.0))
+    );
+
+    save_serializable_to_json(&report, &get_output_file_path("tile_report.json"));
+}
+// END CODE =======================================================================================  END COMPONENT
+
+//
+// * End of file /tile_report.rs
+//
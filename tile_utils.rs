@@ -0,0 +1,839 @@
+///
+/// # tile_utils.rs
+/// This file contains the slippy-map (XYZ) tile calculations:
+/// the tiles covering a track, with a buffer, and the typed manifest of a `z/x/y` tiles folder.
+///
+/// ## Author
+/// * Tom Planche - <github.com/tomPlanche>
+
+// IMPORTS ===================================================================================================  IMPORTS
+use std::collections::{BTreeMap, BTreeSet};
+use std::f64::consts::PI;
+use std::fs::{read_dir, read_to_string, Metadata};
+use std::ops::RangeInclusive;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+use serde::{Deserialize, Serialize};
+
+use crate::file_utils::{get_output_file_path, save_serializable_to_json};
+use crate::gpx_utils::{Coord, WGS84_A};
+use crate::projection_utils::{project, unproject, ProjectedCoord, Projection};
+// END IMPORTS ==========================================================================================   END IMPORTS
+
+// VARIABLES ================================================================================================ VARIABLE
+// Deepest zoom of the tile grids.
+#[allow(dead_code)]
+pub const MAX_ZOOM: u8 = 24;
+
+// Extension of the tile files computed by `track_tiles`.
+#[allow(dead_code)]
+pub const TILE_EXTENSION: &str = "png";
+
+// Default prefix of the `require` paths of the generated modules, the assets folder seen from the app sources.
+#[allow(dead_code)]
+pub const PATH_PREFIX: &str = "../../assets";
+
+// Name of the scan cache file in the 'output' folder.
+#[allow(dead_code)]
+pub const TILE_CACHE_FILE: &str = "tile_cache.json";
+
+// Image extensions accepted for the tile files of a folder.
+const TILE_EXTENSIONS: [&str; 4] = ["png", "jpg", "jpeg", "webp"];
+
+// Number of samples per tile width along the segments, so that no tile crossed between two points is missed.
+const SAMPLES_PER_TILE: f64 = 8.0;
+
+// Map of zooms to the columns (x) to the rows (y) of the tiles
+#[allow(dead_code)]
+pub type TileCoverage = BTreeMap<u8, BTreeMap<u32, BTreeSet<u32>>>;
+
+// Zoom, x and y of a tile
+#[allow(dead_code)]
+pub type TileKey = (u8, u32, u32);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TileFile {
+    pub path: PathBuf,
+    pub bytes: u64,
+}
+
+// Map of the tiles of a `z/x/y` folder to their file
+#[allow(dead_code)]
+pub type TileFiles = BTreeMap<TileKey, TileFile>;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TrailTileReport {
+    pub required: usize,
+    pub present: usize,
+    // As `z/x/y`
+    pub missing: Vec<String>,
+    // Size of the present tiles
+    pub bytes: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TileReport {
+    pub zooms: Vec<u8>,
+    pub buffer: f64,
+    pub trails: BTreeMap<String, TrailTileReport>,
+    // Tiles of the folder required by no trail, as `z/x/y`
+    pub unused: Vec<String>,
+    pub unused_bytes: u64,
+    pub total_bytes: u64,
+}
+
+// The tiles of a `z/x/y` folder or of a coverage, as saved by `tiles_to_json` and `track_tiles`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TileManifest {
+    // Extension of the tile files
+    pub extension: String,
+    // Map of zooms to the columns (x) to the sorted rows (y)
+    pub zooms: BTreeMap<u8, BTreeMap<u32, Vec<u32>>>,
+    pub counts: BTreeMap<u8, usize>,
+    // [west, south, east, north] of the tiles of the highest zoom, None without tiles
+    pub bbox: Option<[f64; 4]>,
+    // Size of the tile files, 0 for a coverage
+    pub total_bytes: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CachedTile {
+    pub y: u32,
+    pub file_name: String,
+    pub bytes: u64,
+    // In nanoseconds since the epoch, None when unknown so the column is always rescanned
+    pub modified: Option<u64>,
+}
+
+// The tiles of a `z/x` folder, as scanned at the modification time of the folder and of each tile
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CachedColumn {
+    // In nanoseconds since the epoch, None when unknown so always rescanned
+    pub modified: Option<u64>,
+    pub tiles: Vec<CachedTile>,
+    pub issues: Vec<String>,
+}
+
+// Map of the `z/x` folders of a tiles folder to their cached scan
+#[allow(dead_code)]
+pub type TileColumns = BTreeMap<String, CachedColumn>;
+
+// Map of the tiles folders (canonical paths) to their columns
+#[allow(dead_code)]
+pub type TileScanCache = BTreeMap<String, TileColumns>;
+// END VARIABLES =======================================================================================  END VARIABLES
+
+// FUNCTIONS ================================================================================================ FUNCTIONS
+///
+/// # tile_size
+/// The width of a tile at a zoom (in Web Mercator meters).
+fn tile_size(zoom: u8) -> f64 {
+    2.0 * PI * WGS84_A / 2f64.powi(zoom as i32)
+}
+
+///
+/// # tile_index
+/// The index of the tile containing a Web Mercator position along an axis, clamped to the map.
+fn tile_index(position: f64, zoom: u8, size: f64) -> u32 {
+    let max_index: f64 = 2f64.powi(zoom as i32) - 1.0;
+
+    ((position / size).floor()).clamp(0.0, max_index) as u32
+}
+
+///
+/// # coord_to_tile
+/// Get the tile containing a coordinate.
+///
+/// ## Arguments
+/// * `coord` - The coordinate in decimal degrees.
+/// * `zoom` - The zoom level.
+///
+/// ## Returns
+/// * `(u32, u32)` - The x and y of the tile, y growing southwards.
+#[allow(dead_code)]
+pub fn coord_to_tile(coord: Coord, zoom: u8) -> (u32, u32) {
+    let projected: ProjectedCoord = project(coord, Projection::WebMercator);
+    let size: f64 = tile_size(zoom);
+    let half_world: f64 = PI * WGS84_A;
+
+    (
+        tile_index(projected.x + half_world, zoom, size),
+        tile_index(half_world - projected.y, zoom, size),
+    )
+}
+
+///
+/// # tile_to_coord
+/// Get the north-west corner of a tile.
+///
+/// ## Arguments
+/// * `tile` - The zoom, x and y of the tile.
+///
+/// ## Returns
+/// * `Coord` - The corner in decimal degrees, `tile_to_coord((z, x + 1, y + 1))` being the south-east one.
+#[allow(dead_code)]
+pub fn tile_to_coord((zoom, x, y): TileKey) -> Coord {
+    let size: f64 = tile_size(zoom);
+    let half_world: f64 = PI * WGS84_A;
+
+    unproject(
+        ProjectedCoord {
+            x: x as f64 * size - half_world,
+            y: half_world - y as f64 * size,
+        },
+        Projection::WebMercator,
+    )
+}
+
+///
+/// # calc_track_tiles
+/// Calculate the tiles covering a track, with every point within a buffer of the track.
+/// The segments are sampled in the Web Mercator plane and the buffer grown by half the sampling step,
+/// so no tile is missed and at most a sixteenth of a tile is added on each side.
+///
+/// ## Arguments
+/// * `coords` - The coordinates of the track.
+/// * `zooms` - The zoom levels.
+/// * `buffer` - The buffer around the track (in meters).
+///
+/// ## Returns
+/// * `TileCoverage` - The tiles of each zoom.
+#[allow(dead_code)]
+pub fn calc_track_tiles(coords: &[Coord], zooms: RangeInclusive<u8>, buffer: f64) -> TileCoverage {
+    let half_world: f64 = PI * WGS84_A;
+
+    // Positions from the north-west corner of the map, with the local scale factor of the projection
+    let positions: Vec<(f64, f64, f64)> = coords
+        .iter()
+        .map(|coord| {
+            let projected: ProjectedCoord = project(*coord, Projection::WebMercator);
+
+            (projected.x + half_world, half_world - projected.y, 1.0 / coord.lat.to_radians().cos())
+        })
+        .collect();
+
+    let mut coverage: TileCoverage = TileCoverage::new();
+
+    for zoom in zooms {
+        let size: f64 = tile_size(zoom);
+        let step: f64 = size / SAMPLES_PER_TILE;
+        let columns = coverage.entry(zoom).or_default();
+
+        let mut add_sample = |x: f64, y: f64, scale: f64| {
+            let margin: f64 = buffer * scale + step / 2.0;
+
+            for column in tile_index(x - margin, zoom, size)..=tile_index(x + margin, zoom, size) {
+                let rows = columns.entry(column).or_default();
+                rows.extend(tile_index(y - margin, zoom, size)..=tile_index(y + margin, zoom, size));
+            }
+        };
+
+        if let Some(&(x, y, scale)) = positions.first() {
+            add_sample(x, y, scale);
+        }
+
+        for pair in positions.windows(2) {
+            let ((x_1, y_1, scale_1), (x_2, y_2, scale_2)) = (pair[0], pair[1]);
+            let samples: usize = ((x_2 - x_1).hypot(y_2 - y_1) / step).ceil().max(1.0) as usize;
+
+            for sample in 1..=samples {
+                let t: f64 = sample as f64 / samples as f64;
+                add_sample(x_1 + (x_2 - x_1) * t, y_1 + (y_2 - y_1) * t, scale_1.max(scale_2));
+            }
+        }
+    }
+
+    coverage
+}
+
+///
+/// # count_tiles
+/// Count the tiles of a coverage.
+#[allow(dead_code)]
+pub fn count_tiles(coverage: &TileCoverage) -> usize {
+    coverage.values().flat_map(|columns| columns.values()).map(|rows| rows.len()).sum()
+}
+
+///
+/// # tile_entries
+/// The numeric entries of a level of a tiles folder, with their path:
+/// the zoom and x folders, or the y image files.
+/// The other entries are skipped and described in the issues.
+///
+/// ## Arguments
+/// * `folder` - The folder of the level.
+/// * `max_index` - The highest number allowed.
+/// * `files` - Whether the entries are the tile files.
+/// * `issues` - The issues to push to.
+///
+/// ## Returns
+/// * `Vec<(u32, PathBuf)>` - The numbers and paths of the valid entries.
+fn tile_entries(folder: &Path, max_index: u32, files: bool, issues: &mut Vec<String>) -> Vec<(u32, PathBuf)> {
+    let entries = match read_dir(folder) {
+        Ok(entries) => entries,
+        Err(error) => {
+            issues.push(format!("{}: could not be read ({})", folder.display(), error));
+            return Vec::new();
+        }
+    };
+
+    let mut numbers: Vec<(u32, PathBuf)> = Vec::new();
+
+    for path in entries.filter_map(|entry| entry.ok()).map(|entry| entry.path()) {
+        if path.is_file() != files {
+            issues.push(format!("{}: expected a {}", path.display(), if files { "tile file" } else { "folder" }));
+            continue;
+        }
+
+        let Some(name) = path.file_name().and_then(|name| name.to_str()) else {
+            issues.push(format!("{}: the name is not UTF-8", path.display()));
+            continue;
+        };
+
+        let number: &str = if files {
+            match name.rsplit_once('.') {
+                Some((stem, extension)) if TILE_EXTENSIONS.contains(&extension.to_lowercase().as_str()) => stem,
+                _ => {
+                    issues.push(format!("{}: not a {} image", path.display(), TILE_EXTENSIONS.join(", ")));
+                    continue;
+                }
+            }
+        } else {
+            name
+        };
+
+        match number.parse::<u32>() {
+            Ok(index) if index <= max_index => numbers.push((index, path)),
+            Ok(_) => issues.push(format!("{}: outside the grid (at most {})", path.display(), max_index)),
+            Err(_) => issues.push(format!("{}: the name is not a number", path.display())),
+        }
+    }
+
+    numbers
+}
+
+///
+/// # modified_time
+/// The modification time of a file or folder, in nanoseconds since the epoch.
+fn modified_time(metadata: &Metadata) -> Option<u64> {
+    metadata
+        .modified()
+        .ok()
+        .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+        .map(|duration| duration.as_nanos() as u64)
+}
+
+///
+/// # is_tile_up_to_date
+/// Whether a cached tile still has the size and modification time of its file, with a single stat.
+fn is_tile_up_to_date(x_path: &Path, tile: &CachedTile) -> bool {
+    tile.modified.is_some()
+        && x_path
+            .join(&tile.file_name)
+            .metadata()
+            .is_ok_and(|metadata| metadata.len() == tile.bytes && modified_time(&metadata) == tile.modified)
+}
+
+///
+/// # scan_tile_column
+/// List the tiles of a `z/x` folder.
+///
+/// ## Arguments
+/// * `x_path` - The folder of the column.
+/// * `max_index` - The highest row of the zoom.
+/// * `modified` - The modification time of the folder, kept in the cache.
+///
+/// ## Returns
+/// * `CachedColumn` - The tiles and the skipped entries of the column.
+fn scan_tile_column(x_path: &Path, max_index: u32, modified: Option<u64>) -> CachedColumn {
+    let mut issues: Vec<String> = Vec::new();
+
+    let tiles: Vec<CachedTile> = tile_entries(x_path, max_index, true, &mut issues)
+        .into_iter()
+        .map(|(y, y_path)| {
+            let metadata: Option<Metadata> = y_path.metadata().ok();
+
+            CachedTile {
+                y,
+                file_name: y_path.file_name().unwrap_or_default().to_string_lossy().into_owned(),
+                bytes: metadata.as_ref().map_or(0, |metadata| metadata.len()),
+                modified: metadata.as_ref().and_then(modified_time),
+            }
+        })
+        .collect();
+
+    CachedColumn { modified, tiles, issues }
+}
+
+///
+/// # scan_tile_folder_cached
+/// List the tiles of a `z/x/y` folder, only rescanning the `z/x` folders modified since the cached scan.
+/// A folder is modified when tiles are added, removed or renamed, so the size and modification time
+/// of each tile are checked too, for the tiles rewritten in place.
+///
+/// ## Arguments
+/// * `folder` - The tiles folder.
+/// * `columns` - The cached columns of the folder, updated.
+///
+/// ## Returns
+/// * `(TileFiles, Vec<String>, usize)` - The tiles, the skipped entries and the number of rescanned columns.
+#[allow(dead_code)]
+pub fn scan_tile_folder_cached(folder: &Path, columns: &mut TileColumns) -> (TileFiles, Vec<String>, usize) {
+    let mut tiles: TileFiles = TileFiles::new();
+    let mut issues: Vec<String> = Vec::new();
+    let mut seen: BTreeSet<String> = BTreeSet::new();
+    let mut rescanned: usize = 0;
+
+    for (zoom, zoom_path) in tile_entries(folder, MAX_ZOOM as u32, false, &mut issues) {
+        let zoom: u8 = zoom as u8;
+        let max_index: u32 = (1 << zoom) - 1;
+
+        for (x, x_path) in tile_entries(&zoom_path, max_index, false, &mut issues) {
+            let key: String = format!("{}/{}", zoom, x);
+            let modified: Option<u64> = x_path.metadata().ok().as_ref().and_then(modified_time);
+
+            let up_to_date: bool = modified.is_some()
+                && columns.get(&key).is_some_and(|column| {
+                    column.modified == modified && column.tiles.iter().all(|tile| is_tile_up_to_date(&x_path, tile))
+                });
+            if !up_to_date {
+                rescanned += 1;
+                columns.insert(key.clone(), scan_tile_column(&x_path, max_index, modified));
+            }
+
+            let column: &CachedColumn = &columns[&key];
+
+            for tile in &column.tiles {
+                tiles.insert((zoom, x, tile.y), TileFile { path: x_path.join(&tile.file_name), bytes: tile.bytes });
+            }
+            issues.extend(column.issues.iter().cloned());
+            seen.insert(key);
+        }
+    }
+
+    // The columns removed from the folder
+    columns.retain(|key, _| seen.contains(key));
+
+    issues.sort();
+
+    (tiles, issues, rescanned)
+}
+
+///
+/// # scan_tile_folder
+/// List the tiles of a `z/x/y` folder, the tree `tiles_to_json` walks.
+/// The entries that are not numeric folders or image files, or outside the grid of their zoom, are skipped.
+///
+/// ## Arguments
+/// * `folder` - The tiles folder.
+///
+/// ## Returns
+/// * `(TileFiles, Vec<String>)` - The tiles, their path and size, and the skipped entries with the reason.
+#[allow(dead_code)]
+pub fn scan_tile_folder(folder: &Path) -> (TileFiles, Vec<String>) {
+    let (tiles, issues, _) = scan_tile_folder_cached(folder, &mut TileColumns::new());
+
+    (tiles, issues)
+}
+
+///
+/// # scan_tile_folder_incremental
+/// List the tiles of a `z/x/y` folder with the scan cache of the 'output' folder,
+/// saving the cache when a column was rescanned or removed.
+///
+/// ## Arguments
+/// * `folder` - The tiles folder.
+/// * `save_cache` - Whether to save the updated cache, false for the runs that must write nothing.
+///
+/// ## Returns
+/// * `(TileFiles, Vec<String>, usize)` - The tiles, the skipped entries and the number of rescanned columns.
+#[allow(dead_code)]
+pub fn scan_tile_folder_incremental(folder: &Path, save_cache: bool) -> (TileFiles, Vec<String>, usize) {
+    let cache_path: PathBuf = get_output_file_path(TILE_CACHE_FILE);
+
+    // A corrupt cache, or one saved by an older version, is scanned again
+    let mut cache: TileScanCache = read_to_string(&cache_path)
+        .ok()
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default();
+
+    let key: String = folder.canonicalize().unwrap_or(folder.to_path_buf()).display().to_string();
+    let columns: &mut TileColumns = cache.entry(key).or_default();
+    let columns_before: usize = columns.len();
+
+    let (tiles, issues, rescanned) = scan_tile_folder_cached(folder, columns);
+
+    if save_cache && (rescanned > 0 || columns.len() != columns_before) {
+        save_serializable_to_json(&cache, &cache_path);
+    }
+
+    (tiles, issues, rescanned)
+}
+
+///
+/// # calc_tiles_bbox
+/// The bounds of the tiles of the highest zoom, the closest to the covered area.
+///
+/// ## Arguments
+/// * `tiles` - The tiles.
+///
+/// ## Returns
+/// * `Option<[f64; 4]>` - [west, south, east, north], None without tiles.
+#[allow(dead_code)]
+pub fn calc_tiles_bbox<'a>(tiles: impl Iterator<Item = &'a TileKey> + Clone) -> Option<[f64; 4]> {
+    let max_zoom: u8 = tiles.clone().map(|(zoom, _, _)| *zoom).max()?;
+    let mut bbox: [f64; 4] = [180.0, 90.0, -180.0, -90.0];
+
+    for (zoom, x, y) in tiles.filter(|(zoom, _, _)| *zoom == max_zoom) {
+        let north_west: Coord = tile_to_coord((*zoom, *x, *y));
+        let south_east: Coord = tile_to_coord((*zoom, x + 1, y + 1));
+
+        bbox = [
+            bbox[0].min(north_west.lon),
+            bbox[1].min(south_east.lat),
+            bbox[2].max(south_east.lon),
+            bbox[3].max(north_west.lat),
+        ];
+    }
+
+    Some(bbox)
+}
+
+impl TileManifest {
+    ///
+    /// # from_tiles
+    /// Build the manifest of a set of tiles.
+    fn from_tiles(tiles: &BTreeSet<TileKey>, extension: String, total_bytes: u64) -> TileManifest {
+        let mut zooms: BTreeMap<u8, BTreeMap<u32, Vec<u32>>> = BTreeMap::new();
+        let mut counts: BTreeMap<u8, usize> = BTreeMap::new();
+
+        // The tiles are sorted, so are the rows
+        for (zoom, x, y) in tiles {
+            zooms.entry(*zoom).or_default().entry(*x).or_default().push(*y);
+            *counts.entry(*zoom).or_default() += 1;
+        }
+
+        TileManifest {
+            extension,
+            zooms,
+            counts,
+            bbox: calc_tiles_bbox(tiles.iter()),
+            total_bytes,
+        }
+    }
+
+    ///
+    /// # from_files
+    /// Build the manifest of the tiles of a folder, with the extension of its first tile.
+    #[allow(dead_code)]
+    pub fn from_files(files: &TileFiles) -> TileManifest {
+        let extension: String = files
+            .values()
+            .find_map(|file| file.path.extension()?.to_str().map(str::to_lowercase))
+            .unwrap_or(TILE_EXTENSION.to_string());
+
+        TileManifest::from_tiles(
+            &files.keys().copied().collect(),
+            extension,
+            files.values().map(|file| file.bytes).sum(),
+        )
+    }
+
+    ///
+    /// # from_coverage
+    /// Build the manifest of the tiles covering a track.
+    #[allow(dead_code)]
+    pub fn from_coverage(coverage: &TileCoverage) -> TileManifest {
+        TileManifest::from_tiles(&coverage_tiles(coverage), TILE_EXTENSION.to_string(), 0)
+    }
+
+    ///
+    /// # tiles
+    /// The tiles of the manifest as `(z, x, y)`, e.g. once loaded again from its JSON.
+    #[allow(dead_code)]
+    pub fn tiles(&self) -> BTreeSet<TileKey> {
+        self.zooms
+            .iter()
+            .flat_map(|(zoom, columns)| {
+                columns
+                    .iter()
+                    .flat_map(move |(x, rows)| rows.iter().map(move |y| (*zoom, *x, *y)))
+            })
+            .collect()
+    }
+}
+
+///
+/// # tiles_to_module
+/// Generate a TypeScript or JavaScript module exporting the tiles of a folder as a nested map,
+/// `{ [z]: { [x]: { [y]: require(...) } } }`, so the bundler picks up every tile.
+/// The tiles are sorted by zoom, x and y, so the output only changes with the folder.
+///
+/// ## Arguments
+/// * `files` - The tiles of the folder, from `scan_tile_folder`.
+/// * `folder` - The tiles folder, removed from the tile paths.
+/// * `prefix` - The path of the folder seen from the module, prepended to the tile paths.
+/// * `typescript` - Whether to type the map.
+///
+/// ## Returns
+/// * `String` - The source of the module.
+#[allow(dead_code)]
+pub fn tiles_to_module(files: &TileFiles, folder: &Path, prefix: &str, typescript: bool) -> String {
+    let mut source: String = String::from("// Generated by tiles_to_module, do not edit.\n\n");
+
+    if typescript {
+        source.push_str("export type TileMap = { [z: number]: { [x: number]: { [y: number]: number } } };\n\n");
+        source.push_str("const tiles: TileMap = {\n");
+    } else {
+        source.push_str("const tiles = {\n");
+    }
+
+    let mut current: Option<(u8, u32)> = None;
+
+    for ((zoom, x, y), file) in files {
+        if current.map(|(current_zoom, _)| current_zoom) != Some(*zoom) {
+            if current.is_some() {
+                source.push_str("    },\n  },\n");
+            }
+            source.push_str(&format!("  {}: {{\n    {}: {{\n", zoom, x));
+        } else if current != Some((*zoom, *x)) {
+            source.push_str(&format!("    }},\n    {}: {{\n", x));
+        }
+        current = Some((*zoom, *x));
+
+        // Relative to the folder, with forward slashes whatever the platform
+        let relative: Vec<String> = file
+            .path
+            .strip_prefix(folder)
+            .unwrap_or(&file.path)
+            .components()
+            .map(|component| component.as_os_str().to_string_lossy().into_owned())
+            .collect();
+        let path: String = format!("{}/{}", prefix.trim_end_matches('/'), relative.join("/"));
+
+        source.push_str(&format!(
+            "      {}: require('{}'),\n",
+            y,
+            path.replace('\\', "\\\\").replace('\'', "\\'")
+        ));
+    }
+
+    if current.is_some() {
+        source.push_str("    },\n  },\n");
+    }
+    source.push_str("};\n\nexport default tiles;\n");
+
+    source
+}
+
+///
+/// # tile_name
+/// The `z/x/y` name of a tile.
+#[allow(dead_code)]
+pub fn tile_name((zoom, x, y): TileKey) -> String {
+    format!("{}/{}/{}", zoom, x, y)
+}
+
+///
+/// # coverage_tiles
+/// The tiles of a coverage as `(z, x, y)`.
+#[allow(dead_code)]
+pub fn coverage_tiles(coverage: &TileCoverage) -> BTreeSet<TileKey> {
+    coverage
+        .iter()
+        .flat_map(|(zoom, columns)| {
+            columns
+                .iter()
+                .flat_map(move |(x, rows)| rows.iter().map(move |y| (*zoom, *x, *y)))
+        })
+        .collect()
+}
+
+///
+/// # build_tile_report
+/// Cross-check the tiles on disk against the coverage of each trail.
+///
+/// ## Arguments
+/// * `files` - The tiles of the folder.
+/// * `coverages` - The coverage of each trail.
+/// * `buffer` - The buffer used for the coverages (in meters), kept in the report.
+///
+/// ## Returns
+/// * `TileReport` - The missing tiles and size per trail, and the unused tiles.
+#[allow(dead_code)]
+pub fn build_tile_report(files: &TileFiles, coverages: &BTreeMap<String, TileCoverage>, buffer: f64) -> TileReport {
+    let mut used: BTreeSet<TileKey> = BTreeSet::new();
+    let mut trails: BTreeMap<String, TrailTileReport> = BTreeMap::new();
+
+    for (trail, coverage) in coverages {
+        let required: BTreeSet<TileKey> = coverage_tiles(coverage);
+        let (present, missing): (Vec<TileKey>, Vec<TileKey>) =
+            required.iter().partition(|tile| files.contains_key(tile));
+
+        trails.insert(
+            trail.clone(),
+            TrailTileReport {
+                required: required.len(),
+                present: present.len(),
+                missing: missing.into_iter().map(tile_name).collect(),
+                bytes: present.iter().map(|tile| files[tile].bytes).sum(),
+            },
+        );
+
+        used.extend(required);
+    }
+
+    let unused: Vec<TileKey> = files.keys().filter(|tile| !used.contains(tile)).copied().collect();
+
+    TileReport {
+        zooms: files.keys().map(|(zoom, _, _)| *zoom).collect::<BTreeSet<u8>>().into_iter().collect(),
+        buffer,
+        trails,
+        unused_bytes: unused.iter().map(|tile| files[tile].bytes).sum(),
+        unused: unused.into_iter().map(tile_name).collect(),
+        total_bytes: files.values().map(|file| file.bytes).sum(),
+    }
+}
+
+#[test]
+fn test_coord_to_tile() {
+    let coord = Coord { lat: 42.68, lon: 0.08 };
+
+    assert_eq!(coord_to_tile(coord, 0), (0, 0));
+    assert_eq!(coord_to_tile(coord, 10), (512, 377));
+    assert_eq!(coord_to_tile(coord, 14), (8195, 6040));
+
+    let corner = tile_to_coord((14, 8195, 6040));
+    assert_eq!(coord_to_tile(corner, 14), (8195, 6040));
+    assert!((corner.lon - 0.065_917_968_75).abs() < 1e-9);
+}
+
+#[test]
+fn test_calc_track_tiles() {
+    // Around the center of the tile (8195, 6040) at zoom 14, about 1.8km wide there
+    let center = Coord { lat: 42.6744, lon: 0.0769 };
+    assert_eq!(coord_to_tile(center, 14), (8195, 6040));
+
+    let coverage = calc_track_tiles(&[center], 14..=14, 0.0);
+    assert_eq!(count_tiles(&coverage), 1);
+
+    // 1km of buffer reaches the 8 neighbours
+    let coverage = calc_track_tiles(&[center], 14..=14, 1000.0);
+    assert_eq!(count_tiles(&coverage), 9);
+
+    // A segment crossing two tiles between its points
+    let end = Coord { lat: 42.6744, lon: 0.12 };
+    let coverage = calc_track_tiles(&[center, end], 13..=14, 0.0);
+    assert_eq!(coverage[&14].keys().copied().collect::<Vec<u32>>(), vec![8195, 8196, 8197]);
+    assert_eq!(coverage[&13].len(), 2);
+
+    let manifest = TileManifest::from_coverage(&coverage);
+    assert_eq!(manifest.zooms[&14][&8196], vec![6040]);
+    assert_eq!(manifest.counts[&14], 3);
+}
+
+#[test]
+fn test_build_tile_report() {
+    let file = |bytes: u64| TileFile { path: PathBuf::new(), bytes };
+    let files: TileFiles = BTreeMap::from([((14, 8195, 6040), file(100)), ((14, 8195, 6041), file(50))]);
+    let coverage = calc_track_tiles(&[Coord { lat: 42.6744, lon: 0.0769 }, Coord { lat: 42.6744, lon: 0.095 }], 14..=14, 0.0);
+
+    let report = build_tile_report(&files, &BTreeMap::from([("trail.gpx".to_string(), coverage)]), 0.0);
+
+    assert_eq!(
+        report.trails["trail.gpx"],
+        TrailTileReport { required: 2, present: 1, missing: vec!["14/8196/6040".to_string()], bytes: 100 }
+    );
+    assert_eq!((report.unused, report.unused_bytes, report.total_bytes), (vec!["14/8195/6041".to_string()], 50, 150));
+}
+
+#[test]
+fn test_scan_tile_folder() {
+    use std::fs::{create_dir_all, remove_dir_all, write};
+
+    // The process id keeps parallel runs apart
+    let folder = std::env::temp_dir().join(format!("tile_utils_test_{}", std::process::id()));
+    let _ = remove_dir_all(&folder);
+    create_dir_all(folder.join("14/8195")).unwrap();
+    create_dir_all(folder.join("2/9")).unwrap();
+    write(folder.join("14/8195/6040.png"), [1u8, 2, 3]).unwrap();
+    write(folder.join("14/8195/6041.txt"), [1u8]).unwrap();
+    write(folder.join("14/8195/north.png"), [1u8]).unwrap();
+    write(folder.join("README.md"), [1u8]).unwrap();
+
+    let (files, issues) = scan_tile_folder(&folder);
+    assert_eq!(files.keys().copied().collect::<Vec<TileKey>>(), vec![(14, 8195, 6040)]);
+    // The text file, the name that is not a number, the column outside the grid and the root file
+    assert_eq!(issues.len(), 4);
+
+    let manifest = TileManifest::from_files(&files);
+    assert_eq!((manifest.extension.as_str(), manifest.counts[&14], manifest.total_bytes), ("png", 1, 3));
+
+    let loaded: TileManifest = serde_json::from_str(&serde_json::to_string(&manifest).unwrap()).unwrap();
+    assert_eq!((&loaded.zooms, &loaded.counts, loaded.total_bytes), (&manifest.zooms, &manifest.counts, 3));
+    assert_eq!(loaded.tiles(), files.keys().copied().collect());
+
+    remove_dir_all(&folder).unwrap();
+}
+
+#[test]
+fn test_tiles_to_module() {
+    let folder = PathBuf::from("tiles");
+    let file = |path: &str| TileFile { path: folder.join(path), bytes: 1 };
+    let files: TileFiles = BTreeMap::from([
+        ((14, 8195, 6041), file("14/8195/6041.png")),
+        ((14, 8195, 6040), file("14/8195/6040.png")),
+        ((14, 8196, 6040), file("14/8196/6040.jpg")),
+        ((9, 10, 11), file("9/10/11.png")),
+    ]);
+
+    assert_eq!(
+        tiles_to_module(&files, &folder, "../../assets/tiles/", false),
+        "// Generated by tiles_to_module, do not edit.\n\n\
+        const tiles = {\n  9: {\n    10: {\n      11: require('../../assets/tiles/9/10/11.png'),\n    },\n  },\n\
+        \x20 14: {\n    8195: {\n      6040: require('../../assets/tiles/14/8195/6040.png'),\n\
+        \x20     6041: require('../../assets/tiles/14/8195/6041.png'),\n    },\n\
+        \x20   8196: {\n      6040: require('../../assets/tiles/14/8196/6040.jpg'),\n    },\n  },\n\
+        };\n\nexport default tiles;\n"
+    );
+    assert!(tiles_to_module(&files, &folder, "..", true).contains("const tiles: TileMap = {"));
+}
+
+#[test]
+fn test_scan_tile_folder_cached() {
+    use std::fs::{create_dir_all, remove_dir_all, write};
+
+    // The process id keeps parallel runs apart
+    let folder = std::env::temp_dir().join(format!("tile_utils_cache_test_{}", std::process::id()));
+    let _ = remove_dir_all(&folder);
+    create_dir_all(folder.join("14/8195")).unwrap();
+    create_dir_all(folder.join("14/8196")).unwrap();
+    write(folder.join("14/8195/6040.png"), [1u8]).unwrap();
+
+    let mut columns = TileColumns::new();
+    assert_eq!(scan_tile_folder_cached(&folder, &mut columns).2, 2);
+    assert_eq!(scan_tile_folder_cached(&folder, &mut columns).2, 0);
+
+    // Only the modified column is rescanned
+    write(folder.join("14/8196/6040.png"), [1u8]).unwrap();
+    let (tiles, _, rescanned) = scan_tile_folder_cached(&folder, &mut columns);
+    assert_eq!((tiles.len(), rescanned), (2, 1));
+
+    // A tile rewritten in place, the folder keeping its modification time
+    write(folder.join("14/8196/6040.png"), [1u8; 8]).unwrap();
+    let (tiles, _, rescanned) = scan_tile_folder_cached(&folder, &mut columns);
+    assert_eq!((tiles[&(14, 8196, 6040)].bytes, rescanned), (8, 1));
+
+    remove_dir_all(folder.join("14/8195")).unwrap();
+    let (tiles, _, _) = scan_tile_folder_cached(&folder, &mut columns);
+    assert_eq!((tiles.len(), columns.len()), (1, 1));
+
+    remove_dir_all(&folder).unwrap();
+}
+
+// END FUNCTIONS =======================================================================================  END FUNCTIONS
+
+//
+// * End of file tile_utils.rs
+//
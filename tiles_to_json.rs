@@ -1,28 +1,42 @@
 ///
 /// # tiles_to_json.rs
-/// This file will be a binary, it will take a folder as an argument and will output a json file.
+/// This file will be a binary, it will take a `z/x/y` tiles folder as an argument
+/// and will output its `TileManifest` as a json file.
+/// The entries that are not numeric folders or image files are skipped and listed.
+/// Only the `z/x` folders modified since the last run, or with a tile rewritten, are rescanned (see `scan_tile_folder_incremental`).
+/// With `--check`, nothing is written and the program exits with 1 if the json file is stale.
 ///
 /// Tom Planche <github.com/tomPlanche>
 
 // Imports  ==============================================================================  Imports
+#[path = "gpx_utils.rs"]
+mod gpx_utils;
+
 #[path = "file_utils.rs"]
 mod file_utils;
 
-use json::JsonValue;
+#[path = "utils.rs"]
+mod utils;
+
+#[path = "projection_utils.rs"]
+mod projection_utils;
+
+#[path = "tile_utils.rs"]
+mod tile_utils;
+
+use ansi_term::Colour::{Green, Red, Yellow};
 
 use std::{
     env::{
         current_dir,
         args,
     },
-    fs::{
-        File
-    },
-    io::Write,
     path::Path,
+    process::exit,
 };
 
-use crate::file_utils::{iterate_over_folder, Mode};
+use crate::file_utils::{is_file_up_to_date, save_serializable_to_json};
+use crate::tile_utils::{scan_tile_folder_incremental, TileManifest};
 // Variables  =========================================================================== Variables
 
 // Functions  =========================================================================== Functions
@@ -30,13 +44,17 @@ fn main() {
     // Folder caller - the folder from which the program was called
     let caller = current_dir().unwrap();
 
+    // Whether to only check the json file, the other arguments are positional
+    let check = args().any(|arg| arg == "--check");
+    let mut positional = args().skip(1).filter(|arg| arg != "--check");
+
     // Folder to read from
-    let folder_path = match args().nth(1) {
+    let folder_path = match positional.next() {
         Some(folder_path) => folder_path,
         None => panic!("Please provide a folder path"),
     };
 
-    let file_destination = match args().nth(2) {
+    let file_destination = match positional.next() {
         Some(file_destination) => {
             if !file_destination.ends_with(".json") {
                 panic!("The file destination must end with .json");
@@ -51,23 +69,33 @@ fn main() {
         panic!("The folder {:?} does not exist", folder_path);
     }
 
-    let mut final_json: JsonValue = json::object! {};
+    // List the z/x/y tiles of the folder, with the skipped entries
+    let (files, issues, rescanned) = scan_tile_folder_incremental(Path::new(&folder_path), !check);
+    println!("{} tile folders rescanned", rescanned);
 
-    // Iterate over the folder.
-    // If the element is a file, add it to the final json.
-    // If the element is a folder, add it to the final json and iterate over it.
-    let final_json = iterate_over_folder(
-        final_json,
-        Path::new(&folder_path),
-        Mode::Tiles,
-        None
-    );
+    for issue in &issues {
+        println!("{}", Yellow.paint(format!("Skipped {}", issue)));
+    }
+
+    let manifest = TileManifest::from_files(&files);
 
-    // create/recreate the output file
-    let mut file = File::create(format!("{}/{}", caller.display(), file_destination)).unwrap();
+    for (zoom, count) in &manifest.counts {
+        println!("zoom {}: {} tiles", zoom, count);
+    }
+    println!("{} tiles ({:.1} MB)", files.len(), manifest.total_bytes as f64 / 1_000_000.0);
 
-    // write the final json to the file
-    file.write_all(final_json.dump().as_bytes()).unwrap();
+    let destination = caller.join(file_destination);
+
+    if check {
+        if is_file_up_to_date(&destination, &serde_json::to_string(&manifest).unwrap()) {
+            println!("{}", Green.paint(format!("{} is up to date", destination.display())));
+        } else {
+            println!("{}", Red.paint(format!("{} is stale, run tiles_to_json {} to regenerate it", destination.display(), folder_path)));
+            exit(1);
+        }
+    } else {
+        save_serializable_to_json(&manifest, &destination);
+    }
 }
 
 /*
@@ -0,0 +1,121 @@
+/// # tiles_to_module.rs
+/// This binary generates the TypeScript or JavaScript module requiring every tile of a `z/x/y` tiles folder,
+/// exporting them as a nested map: `{ [z]: { [x]: { [y]: require(...) } } }`, sorted by zoom, x and y.
+/// The entries that are not numeric folders or image files are skipped.
+///
+/// Arguments:
+/// * tiles_folder - The tiles folder.
+/// * destination (optional) - The '.ts' or '.js' module, 'output/tiles.ts' by default.
+/// * --prefix (optional) - The path of the tiles folder seen from the module,
+///   '../../assets/<folder name>' by default.
+/// * --check (optional) - Write nothing and exit with 1 if the module is stale.
+///
+/// Only the `z/x` folders modified since the last run, or with a tile rewritten, are rescanned (see `scan_tile_folder_incremental`).
+///
+/// ## Author
+/// * Tom Planche - <github.com/tomPlanche>
+///
+/// ## Example
+/// >> cargo run --bin tiles_to_module ../app/assets/Chupaca output/chupaca.js --prefix ../../assets/Chupaca
+/// 4 tiles required
+/// Successfully saved to: output/chupaca.js
+
+// IMPORTS ===================================================================================================  IMPORTS
+#[path = "gpx_utils.rs"]
+mod gpx_utils;
+
+#[path = "file_utils.rs"]
+mod file_utils;
+
+#[path = "utils.rs"]
+mod utils;
+
+#[path = "projection_utils.rs"]
+mod projection_utils;
+
+#[path = "tile_utils.rs"]
+mod tile_utils;
+
+use std::fs::write;
+use std::path::{Path, PathBuf};
+use std::process::exit;
+
+use ansi_term::Colour::{Green, Red, Yellow};
+
+use crate::file_utils::is_file_up_to_date;
+use crate::tile_utils::{scan_tile_folder_incremental, tiles_to_module, TileFiles, PATH_PREFIX};
+// END IMPORTS ==========================================================================================   END IMPORTS
+
+// CODE ========================================================================================================= CODE
+fn main() {
+    let args: Vec<String> = std::env::args().collect();
+
+    if args.len() < 2 {
+        panic!("This binary takes a tiles folder, then optionally a '.ts' or '.js' destination, '--prefix <prefix>' and '--check'");
+    }
+
+    let tiles_folder: &Path = Path::new(&args[1]);
+    if !tiles_folder.exists() {
+        panic!("The folder {:?} does not exist", tiles_folder);
+    }
+
+    let mut destination: PathBuf = PathBuf::from("output/tiles.ts");
+    let mut prefix: Option<String> = None;
+    let mut check: bool = false;
+
+    let mut rest = args[2..].iter();
+    while let Some(arg) = rest.next() {
+        match arg.as_str() {
+            "--prefix" => match rest.next() {
+                Some(value) => prefix = Some(value.clone()),
+                None => panic!("The '--prefix' option takes a path"),
+            },
+            "--check" => check = true,
+            other if other.ends_with(".ts") || other.ends_with(".js") => destination = PathBuf::from(other),
+            other => panic!("Unknown argument {:?}, expected a '.ts' or '.js' destination or an option", other),
+        }
+    }
+
+    // The folder is expected right in the assets folder of the app
+    let prefix: String = prefix.unwrap_or_else(|| {
+        let folder_name = tiles_folder.canonicalize().ok().and_then(|path| path.file_name().map(|name| name.to_string_lossy().into_owned()));
+
+        format!("{}/{}", PATH_PREFIX, folder_name.unwrap_or_default())
+    });
+
+    let (files, issues, rescanned): (TileFiles, Vec<String>, usize) = scan_tile_folder_incremental(tiles_folder, !check);
+    println!("{} tile folders rescanned", rescanned);
+    for issue in &issues {
+        println!("{}", Yellow.paint(format!("Skipped {}", issue)));
+    }
+
+    if files.is_empty() {
+        println!("{}", Red.paint("The tiles folder does not contain any z/x/y tile"));
+        return;
+    }
+    println!("{} tiles required", files.len());
+
+    let typescript: bool = destination.extension().is_some_and(|extension| extension == "ts");
+
+    let module: String = tiles_to_module(&files, tiles_folder, &prefix, typescript);
+
+    if check {
+        if is_file_up_to_date(&destination, &module) {
+            println!("{}", Green.paint(format!("{} is up to date", destination.display())));
+        } else {
+            println!("{}", Red.paint(format!("{} is stale, run tiles_to_module to regenerate it", destination.display())));
+            exit(1);
+        }
+        return;
+    }
+
+    match write(&destination, module) {
+        Ok(_) => println!("Successfully saved to: {}", destination.display()),
+        Err(error) => panic!("Could not write the file {:?}: {}", destination, error),
+    }
+}
+// END CODE =======================================================================================  END COMPONENT
+
+//
+// * End of file /tiles_to_module.rs
+//
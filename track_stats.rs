@@ -0,0 +1,77 @@
+/// # track_stats.rs
+/// This binary computes the statistics of each GPX file in the 'assets' folder
+/// and saves them in the 'stats.json' file of the 'output' folder.
+///
+/// The final map will look like this:
+/// ```json
+/// {
+///   "file_1": {
+///     "points": 501,
+///     "length": 13314.2,
+///     "unique_length": 7784.1,
+///     "ascent": 0.0,
+///     "descent": 0.0,
+///     "shape": { "shape": "loop", "start_end_gap": 12.3, "retrace_ratio": 0.02 }, // or out_and_back, point_to_point, unknown
+///     "self_overlaps": [{ "first": [0, 95], "second": [400, 500] }, ...]
+///   },
+///   ...
+/// }
+/// ```
+///
+/// ## Author
+/// * Tom Planche - <github.com/tomPlanche>
+
+// IMPORTS ===================================================================================================  IMPORTS
+#[path = "gpx_utils.rs"]
+mod gpx_utils;
+
+#[path = "file_utils.rs"]
+mod file_utils;
+
+#[path = "utils.rs"]
+mod utils;
+
+#[path = "elevation_utils.rs"]
+mod elevation_utils;
+
+#[path = "analysis_utils.rs"]
+mod analysis_utils;
+
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use crate::analysis_utils::{calc_track_stats, TrackStats};
+use crate::file_utils::{get_output_file_path, look_4_files, read_file_name, read_gpx_points, save_serializable_to_json};
+use crate::gpx_utils::Point;
+// END IMPORTS ==========================================================================================   END IMPORTS
+
+// CODE ========================================================================================================= CODE
+fn main() {
+    // Map of file names to their statistics, sorted by name
+    let mut stats_map: BTreeMap<String, TrackStats> = BTreeMap::new();
+
+    let gpx_files: Vec<PathBuf> = look_4_files();
+    for file in &gpx_files {
+        let file_name: String = match read_file_name(file) {
+            Some(file_name) => file_name,
+            None => panic!("Could not read the file name of {:?}", file),
+        };
+
+        let points: Vec<Point> = match read_gpx_points(file) {
+            Some(points) => points,
+            None => panic!("Could not read the file {:?}", file),
+        };
+
+        let stats: TrackStats = calc_track_stats(&points);
+        println!("{}: {:?}", file_name, stats.shape.shape);
+
+        stats_map.insert(file_name, stats);
+    }
+
+    save_serializable_to_json(&stats_map, &get_output_file_path("stats.json"));
+}
+// END CODE =======================================================================================  END COMPONENT
+
+//
+// * End of file /track_stats.rs
+//
@@ -0,0 +1,107 @@
+/// # track_tiles.rs
+/// This binary computes the slippy-map (XYZ) tiles covering each GPX file, with a buffer,
+/// so the app knows which offline tiles to bundle per trail.
+/// The tiles are saved in a '<file>_tiles.json' file of the 'output' folder,
+/// as the same `TileManifest` as the `tiles_to_json` output.
+///
+/// Arguments:
+/// * min_zoom and max_zoom - The zoom levels, both included.
+/// * buffer (optional) - The buffer around the track (in meters), 200 by default.
+/// * files (optional) - The names of GPX files in the 'assets' folder, all of them by default.
+///
+/// ## Author
+/// * Tom Planche - <github.com/tomPlanche>
+///
+/// ## Example
+/// >> cargo run --bin track_tiles 12 16 100 Balcon2-1.gpx
+/// Balcon2-1.gpx: 47 tiles
+/// Successfully saved to: output/Balcon2-1_tiles.json
+
+// IMPORTS ===================================================================================================  IMPORTS
+#[path = "gpx_utils.rs"]
+mod gpx_utils;
+
+#[path = "file_utils.rs"]
+mod file_utils;
+
+#[path = "utils.rs"]
+mod utils;
+
+#[path = "projection_utils.rs"]
+mod projection_utils;
+
+#[path = "tile_utils.rs"]
+mod tile_utils;
+
+use std::path::PathBuf;
+
+use crate::file_utils::{
+    file_name_to_path_buf,
+    file_stem,
+    get_output_file_path,
+    look_4_files,
+    read_file_name,
+    read_gpx_file,
+    save_serializable_to_json,
+};
+use crate::gpx_utils::Coord;
+use crate::tile_utils::{calc_track_tiles, count_tiles, TileCoverage, TileManifest, MAX_ZOOM};
+// END IMPORTS ==========================================================================================   END IMPORTS
+
+// VARIABLES ================================================================================================ VARIABLE
+const DEFAULT_BUFFER: f64 = 200.0;
+// END VARIABLES =======================================================================================  END VARIABLES
+
+// CODE ========================================================================================================= CODE
+fn main() {
+    let args: Vec<String> = std::env::args().collect();
+
+    if args.len() < 3 {
+        panic!("This binary takes a min and a max zoom, then optionally a buffer and GPX file names");
+    }
+
+    let min_zoom: u8 = args[1].parse().expect("The min zoom must be a number");
+    let max_zoom: u8 = args[2].parse().expect("The max zoom must be a number");
+    if min_zoom > max_zoom || max_zoom > MAX_ZOOM {
+        panic!("The zooms must be ordered and at most {}", MAX_ZOOM);
+    }
+
+    let mut rest: &[String] = &args[3..];
+    let buffer: f64 = match rest.first() {
+        Some(buffer) if !buffer.ends_with(".gpx") => {
+            rest = &rest[1..];
+            buffer.parse().expect("The buffer must be a number")
+        }
+        _ => DEFAULT_BUFFER,
+    };
+
+    let mut gpx_files: Vec<PathBuf> = rest.iter().map(|file_name| file_name_to_path_buf(file_name)).collect();
+    if gpx_files.is_empty() {
+        gpx_files = look_4_files();
+    }
+
+    for file in &gpx_files {
+        let file_name: String = match read_file_name(file) {
+            Some(file_name) => file_name,
+            None => panic!("Could not read the file name of {:?}", file),
+        };
+
+        let coords: Vec<Coord> = match read_gpx_file(file) {
+            Some(coords) => coords,
+            None => panic!("Could not read the file {:?}", file),
+        };
+
+        let coverage: TileCoverage = calc_track_tiles(&coords, min_zoom..=max_zoom, buffer);
+        println!("{}: {} tiles", file_name, count_tiles(&coverage));
+
+        save_serializable_to_json(
+            &TileManifest::from_coverage(&coverage),
+            &get_output_file_path(&format!("{}_tiles.json", file_stem(&file_name))),
+        );
+    }
+}
+// END CODE =======================================================================================  END COMPONENT
+
+//
+// * End of file /track_tiles.rs
+//
@@ -0,0 +1,96 @@
+/// # trail_graph.rs
+/// This binary builds the trail network graph from the GPX files in the 'assets' folder
+/// and the common coordinates found by the comparator ('final.json').
+///
+/// The graph is saved twice in the 'output' folder:
+/// * `graph.json` - The nodes and edges.
+/// * `graph.geojson` - A GeoJSON FeatureCollection to display the network on a map.
+///
+/// ## Author
+/// * Tom Planche - <github.com/tomPlanche>
+///
+/// ## Example
+/// >> cargo run --bin trail_graph
+/// Successfully saved to: output/graph.json
+/// Successfully saved to: output/graph.geojson
+
+// IMPORTS ===================================================================================================  IMPORTS
+#[path = "gpx_utils.rs"]
+mod gpx_utils;
+
+#[path = "file_utils.rs"]
+mod file_utils;
+
+#[path = "utils.rs"]
+mod utils;
+
+#[path = "elevation_utils.rs"]
+mod elevation_utils;
+
+#[path = "graph_utils.rs"]
+mod graph_utils;
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use ansi_term::Colour::{Green, Red};
+
+use crate::file_utils::{
+    get_final_json_path,
+    get_output_file_path,
+    load_from_json,
+    look_4_files,
+    read_file_name,
+    read_gpx_points,
+    save_serializable_to_json,
+};
+use crate::gpx_utils::Point;
+use crate::graph_utils::{build_trail_graph, graph_to_geojson, TrailGraph};
+use crate::utils::FileCoordsHM;
+// END IMPORTS ==========================================================================================   END IMPORTS
+
+// CODE ========================================================================================================= CODE
+fn main() {
+    if !get_final_json_path().exists() {
+        println!("{}", Red.paint("The output file does not exist"));
+        println!("{}", Green.paint("Run the comparator first"));
+
+        return;
+    }
+
+    let overlaps: FileCoordsHM = load_from_json(&get_final_json_path());
+
+    // Map of file names to Vec<Point>
+    let mut tracks: HashMap<String, Vec<Point>> = HashMap::new();
+
+    let gpx_files: Vec<PathBuf> = look_4_files();
+    for file in &gpx_files {
+        let file_name: String = match read_file_name(file) {
+            Some(file_name) => file_name,
+            None => panic!("Could not read the file name of {:?}", file),
+        };
+
+        let points: Vec<Point> = match read_gpx_points(file) {
+            Some(points) => points,
+            None => panic!("Could not read the file {:?}", file),
+        };
+
+        tracks.insert(file_name, points);
+    }
+
+    let graph: TrailGraph = build_trail_graph(&tracks, &overlaps);
+
+    println!(
+        "{} junctions and {} edges found",
+        graph.nodes.len(),
+        graph.edges.len()
+    );
+
+    save_serializable_to_json(&graph, &get_output_file_path("graph.json"));
+    save_serializable_to_json(&graph_to_geojson(&graph), &get_output_file_path("graph.geojson"));
+}
+// END CODE =======================================================================================  END COMPONENT
+
+//
+// * End of file /trail_graph.rs
+//
@@ -7,13 +7,17 @@
 ///
 
 // IMPORTS ===================================================================================================  IMPORTS
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 // END IMPORTS ==========================================================================================   END IMPORTS
 
 // VARIABLES ================================================================================================ VARIABLE
 #[allow(dead_code)]
 pub type FileCoordsHM = HashMap<String, HashMap<String, Vec<(usize, usize)>>>;
 
+// Map of file names to the hash of their content
+#[allow(dead_code)]
+pub type FileHashes = BTreeMap<String, String>;
+
 #[allow(dead_code)]
 pub enum VecOrHashMap<T, U> {
     Vec(Vec<T>),
@@ -58,6 +62,49 @@ where T: Clone {
     unique_pairs
 }
 
+///
+/// # remove_file_from_map
+/// Remove a file from the common coordinates map, as the first or the second file of its pairs.
+///
+/// ## Arguments
+/// * `file_coords_map` - The common coordinates map
+/// * `file_name` - The name of the file to remove
+#[allow(dead_code)]
+pub fn remove_file_from_map(file_coords_map: &mut FileCoordsHM, file_name: &str) {
+    file_coords_map.remove(file_name);
+
+    for other_files in file_coords_map.values_mut() {
+        other_files.remove(file_name);
+    }
+
+    // Files only sharing coordinates with the removed one
+    file_coords_map.retain(|_, other_files| !other_files.is_empty());
+}
+
+///
+/// # find_changed_files
+/// Find the files added, modified or removed between two manifests of file hashes.
+///
+/// ## Arguments
+/// * `previous` - The hashes of the previous run
+/// * `current` - The hashes of the files now
+///
+/// ## Returns
+/// * `Vec<String>` - The sorted names of the changed files
+#[allow(dead_code)]
+pub fn find_changed_files(previous: &FileHashes, current: &FileHashes) -> Vec<String> {
+    let mut changed: Vec<String> = current
+        .iter()
+        .filter(|(file_name, hash)| previous.get(*file_name) != Some(*hash))
+        .map(|(file_name, _)| file_name.clone())
+        .collect();
+
+    changed.extend(previous.keys().filter(|file_name| !current.contains_key(*file_name)).cloned());
+    changed.sort();
+
+    changed
+}
+
 
 #[allow(dead_code)]
 pub(crate) fn print_type_of<T>(_: &T) {
@@ -74,6 +121,37 @@ fn test_get_unique_pairs() {
     ]);
 }
 
+#[test]
+fn test_remove_file_from_map() {
+    let mut file_coords_map: FileCoordsHM = HashMap::from([
+        ("a.gpx".to_string(), HashMap::from([("b.gpx".to_string(), vec![(0, 1)]), ("c.gpx".to_string(), vec![(2, 3)])])),
+        ("b.gpx".to_string(), HashMap::from([("c.gpx".to_string(), vec![(4, 5)])])),
+    ]);
+
+    remove_file_from_map(&mut file_coords_map, "c.gpx");
+
+    assert_eq!(file_coords_map, HashMap::from([
+        ("a.gpx".to_string(), HashMap::from([("b.gpx".to_string(), vec![(0, 1)])])),
+    ]));
+}
+
+#[test]
+fn test_find_changed_files() {
+    let previous: FileHashes = BTreeMap::from([
+        ("a.gpx".to_string(), "1".to_string()),
+        ("b.gpx".to_string(), "2".to_string()),
+        ("c.gpx".to_string(), "3".to_string()),
+    ]);
+    let current: FileHashes = BTreeMap::from([
+        ("a.gpx".to_string(), "1".to_string()),
+        ("b.gpx".to_string(), "4".to_string()),
+        ("d.gpx".to_string(), "5".to_string()),
+    ]);
+
+    assert_eq!(find_changed_files(&previous, &current), vec!["b.gpx", "c.gpx", "d.gpx"]);
+    assert!(find_changed_files(&current, &current).is_empty());
+}
+
 // END FUNCTIONS =======================================================================================  END FUNCTIONS
 
 //
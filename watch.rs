@@ -0,0 +1,209 @@
+/// # watch.rs
+/// This binary watches the 'assets' folder, and optionally a tiles folder,
+/// and re-runs only the pipeline steps affected by a change:
+/// * a GPX file added or modified - `gpx_to_json` and `comparator --update` for this file only,
+/// * a GPX file removed - its json file is deleted and `comparator --update` drops its common coordinates,
+/// * a tile added, modified or removed - `tiles_to_json`, which only rescans the modified folders.
+///
+/// The steps run the binaries built next to this one, once the folders are quiet for half a second.
+///
+/// Arguments:
+/// * tiles_folder (optional) - The `z/x/y` tiles folder to watch.
+/// * destination (optional) - The json file of `tiles_to_json`, 'output/tiles_struct.json' by default.
+///
+/// ## Author
+/// * Tom Planche - <github.com/tomPlanche>
+///
+/// ## Example
+/// >> cargo build && cargo run --bin watch ../app/assets/tiles
+/// Watching ./assets and ../app/assets/tiles, press Ctrl+C to stop
+/// Running gpx_to_json Balcon2-1.gpx
+/// Running comparator --update Balcon2-1.gpx
+/// ...
+
+// IMPORTS ===================================================================================================  IMPORTS
+#[path = "gpx_utils.rs"]
+mod gpx_utils;
+
+#[path = "file_utils.rs"]
+mod file_utils;
+
+#[path = "utils.rs"]
+mod utils;
+
+use std::collections::BTreeSet;
+use std::env::current_exe;
+use std::fs::remove_file;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::mpsc::channel;
+use std::time::Duration;
+
+use ansi_term::Colour::{Green, Red};
+use notify::{recommended_watcher, Event, EventKind, RecursiveMode, Watcher};
+
+use crate::file_utils::file_stem;
+// END IMPORTS ==========================================================================================   END IMPORTS
+
+// VARIABLES ================================================================================================ VARIABLE
+// Folder of the GPX files, as read by `look_4_files`.
+const ASSETS_FOLDER: &str = "./assets";
+
+// Time without events before running the steps, so a copy of many files runs them once.
+const QUIET_DELAY: Duration = Duration::from_millis(500);
+// END VARIABLES =======================================================================================  END VARIABLES
+
+// FUNCTIONS ================================================================================================ FUNCTIONS
+///
+/// # run_step
+/// Run one of the binaries built next to this one.
+///
+/// ## Arguments
+/// * `binary` - The name of the binary.
+/// * `args` - Its arguments.
+///
+/// ## Returns
+/// * `bool` - True if the binary ran successfully, false otherwise.
+fn run_step(binary: &str, args: &[String]) -> bool {
+    let path: PathBuf = match current_exe() {
+        Ok(exe) => exe.with_file_name(binary),
+        Err(error) => panic!("Could not find the watch binary: {}", error),
+    };
+
+    if !path.exists() {
+        println!("{}", Red.paint(format!("The {} binary is missing, run 'cargo build' first", binary)));
+        return false;
+    }
+
+    println!("{}", Green.paint(format!("Running {} {}", binary, args.join(" "))));
+
+    match Command::new(&path).args(args).status() {
+        Ok(status) if status.success() => true,
+        Ok(status) => {
+            println!("{}", Red.paint(format!("{} failed ({})", binary, status)));
+            false
+        }
+        Err(error) => {
+            println!("{}", Red.paint(format!("Could not run {}: {}", binary, error)));
+            false
+        }
+    }
+}
+
+///
+/// # update_gpx_files
+/// Run the steps of the added, modified or removed GPX files.
+///
+/// ## Arguments
+/// * `file_names` - The names of the GPX files in the 'assets' folder.
+fn update_gpx_files(file_names: &BTreeSet<String>) {
+    let (present, removed): (Vec<String>, Vec<String>) = file_names
+        .iter()
+        .cloned()
+        .partition(|file_name| Path::new(ASSETS_FOLDER).join(file_name).exists());
+
+    for file_name in &removed {
+        // The json file written by `gpx_to_json`
+        let json_path: PathBuf = PathBuf::from(format!("./output/{}.json", file_stem(file_name)));
+
+        if json_path.exists() && remove_file(&json_path).is_ok() {
+            println!("Removed {}", json_path.display());
+        }
+    }
+
+    if !present.is_empty() && !run_step("gpx_to_json", &present) {
+        return;
+    }
+
+    let mut update_args: Vec<String> = vec!["--update".to_string()];
+    update_args.extend(file_names.iter().cloned());
+    run_step("comparator", &update_args);
+}
+// END FUNCTIONS =======================================================================================  END FUNCTIONS
+
+// CODE ========================================================================================================= CODE
+fn main() {
+    let args: Vec<String> = std::env::args().collect();
+
+    let assets_folder: PathBuf = match Path::new(ASSETS_FOLDER).canonicalize() {
+        Ok(folder) => folder,
+        Err(_) => panic!("The folder {:?} does not exist, run the binary from the project root", ASSETS_FOLDER),
+    };
+
+    // The events have the paths of the watched folders, canonical to compare them
+    let tiles_folder: Option<PathBuf> = args.get(1).map(|folder| match Path::new(folder).canonicalize() {
+        Ok(folder) => folder,
+        Err(_) => panic!("The folder {:?} does not exist", folder),
+    });
+    let tiles_args: Vec<String> = args[1..].to_vec();
+
+    let (sender, receiver) = channel::<notify::Result<Event>>();
+    let mut watcher = match recommended_watcher(sender) {
+        Ok(watcher) => watcher,
+        Err(error) => panic!("Could not start watching: {}", error),
+    };
+
+    if let Err(error) = watcher.watch(&assets_folder, RecursiveMode::NonRecursive) {
+        panic!("Could not watch {:?}: {}", assets_folder, error);
+    }
+    if let Some(tiles_folder) = &tiles_folder {
+        if let Err(error) = watcher.watch(tiles_folder, RecursiveMode::Recursive) {
+            panic!("Could not watch {:?}: {}", tiles_folder, error);
+        }
+    }
+
+    match args.get(1) {
+        Some(folder) => println!("Watching {} and {}, press Ctrl+C to stop", ASSETS_FOLDER, folder),
+        None => println!("Watching {}, press Ctrl+C to stop", ASSETS_FOLDER),
+    }
+
+    // Wait for a first event, then for the folders to be quiet
+    while let Ok(first_event) = receiver.recv() {
+        let mut events: Vec<notify::Result<Event>> = vec![first_event];
+        while let Ok(event) = receiver.recv_timeout(QUIET_DELAY) {
+            events.push(event);
+        }
+
+        let mut gpx_files: BTreeSet<String> = BTreeSet::new();
+        let mut tiles_changed: bool = false;
+
+        for event in events {
+            let event: Event = match event {
+                Ok(event) => event,
+                Err(error) => {
+                    println!("{}", Red.paint(format!("Watch error: {}", error)));
+                    continue;
+                }
+            };
+
+            // Reading a file changes nothing
+            if matches!(event.kind, EventKind::Access(_)) {
+                continue;
+            }
+
+            for path in event.paths {
+                if tiles_folder.as_ref().is_some_and(|tiles_folder| path.starts_with(tiles_folder)) {
+                    tiles_changed = true;
+                } else if path.parent() == Some(assets_folder.as_path())
+                    && path.extension().is_some_and(|extension| extension == "gpx")
+                {
+                    if let Some(file_name) = path.file_name().and_then(|file_name| file_name.to_str()) {
+                        gpx_files.insert(file_name.to_string());
+                    }
+                }
+            }
+        }
+
+        if !gpx_files.is_empty() {
+            update_gpx_files(&gpx_files);
+        }
+        if tiles_changed {
+            run_step("tiles_to_json", &tiles_args);
+        }
+    }
+}
+// END CODE =======================================================================================  END COMPONENT
+
+//
+// * End of file /watch.rs
+//